@@ -1,116 +1,195 @@
 mod builder;
+mod engine;
 mod flip_mode;
+mod hint_mode;
+mod input;
+mod machine;
+mod matching;
 mod nulos;
+mod scheduler;
+mod tries;
 
-use std::num::NonZeroU32;
+use std::io;
+use std::time::{Duration, Instant};
 
 use rand::Rng;
-use std::io::{self, Stdin};
+use serde::{Deserialize, Serialize};
 
-use crate::{card::Card, deck::Deck};
+use crate::{card::Card, history::History, session};
 
 pub use builder::AskerBuilder;
-pub use flip_mode::FlipMode;
-use nulos::Nulos;
+pub use engine::{Engine, Outcome, Question};
+pub use flip_mode::{FlipMode, RandomFlip};
+pub use hint_mode::HintMode;
+pub use machine::{Event, Message};
+use input::{Input, Line};
+pub use matching::Matching;
+pub use scheduler::{
+    LeitnerScheduler, Passes, Scheduler, SchedulerCheckpoint, SchedulerKind, Sequential,
+    SpacedRepetition, Weighted, WithReplacement,
+};
+pub use tries::Tries;
+
+// Typed by the user to give up a question and see its answer
+const GIVE_UP: &str = ":giveup";
 
 pub trait Ask {
     fn advance(&mut self) {}
     fn get_card(&self) -> (&Card, usize); // Card / id
 }
 
+/// Ask the questions of an `Engine` in the terminal.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "R: Rng + Default"))]
 pub struct Asker<R: Rng> {
-    deck: Deck,
-    failed: Nulos,
-    all_cases: bool,
-    cycle_counter: u32,
-    max_cycle: NonZeroU32,
-    tries: NonZeroU32,
-    stat: Stat,
-    rng: R,
+    engine: Engine<R>,
+    copy: bool,
+    time_limit: Option<Duration>,
+    session_time_limit: Option<Duration>,
+    // Save the session after each answer
+    #[serde(skip)]
+    checkpoint: bool,
 }
 
-impl<R: Rng> Ask for Asker<R> {
-    fn advance(&mut self) {
-        if matches!(self.stat, Stat::New) {
-            if self.deck.question_index() + 1 == self.deck.len() {
-                if self.failed.is_empty() {
-                    self.cycle_counter += 1;
-                    self.deck.suffle(&mut self.rng);
-                    if self.all_cases {
-                        self.deck.flip_all();
-                    }
-                    self.deck.advance();
-                } else {
-                    self.stat = Stat::Failed;
-                    self.failed.advance(&mut self.rng);
-                }
-            } else {
-                self.deck.advance()
-            }
-        } else if self.failed.is_empty() {
-            self.stat = Stat::New;
-            self.advance();
-        } else {
-            self.failed.advance(&mut self.rng);
+impl<R: Rng> Asker<R> {
+    pub fn run(mut self) -> Result<(), io::Error> {
+        if self.engine.next_question().is_none() {
+            println!("There is no card to ask.");
+            // The scheduler still counts the session
+            self.end(None)?;
+            return Ok(());
         }
-    }
 
-    fn get_card(&self) -> (&Card, usize) {
-        if matches!(self.stat, Stat::New) {
-            self.deck.get_card()
-        } else {
-            let index = self.failed.get().unwrap();
-            (self.deck.get_card_by_index(index).unwrap(), index)
+        let input = Input::stdin();
+        let session_deadline = self.session_time_limit.map(|limit| Instant::now() + limit);
+
+        if matches!(self.engine.tries(), Tries::Unlimited) {
+            println!("Type `{}` to see the answer.\n", GIVE_UP);
+        }
+
+        while !self.engine.is_over() {
+            self.save_checkpoint()?;
+            if !self.ask(&input, session_deadline) {
+                break;
+            }
         }
+
+        self.end(session_deadline)?;
+
+        Ok(())
     }
-}
 
-impl<R: Rng> Asker<R> {
-    pub fn run(mut self) -> Result<(), io::Error> {
-        let mut stdin = io::stdin();
+    // Save the session, keeping the checkpoint if the user stopped before its end.
+    // Return false if the session was interrupted.
+    fn end(&mut self, session_deadline: Option<Instant>) -> Result<bool, io::Error> {
+        self.engine.finish()?;
 
-        while self.cycle_counter < self.max_cycle.get() {
-            self.ask(&mut stdin)?;
-            self.advance();
+        let interrupted = !self.engine.is_over()
+            && session_deadline.is_none_or(|deadline| Instant::now() < deadline);
+        if self.checkpoint && !interrupted {
+            session::remove()?;
         }
 
-        Ok(())
+        Ok(!interrupted)
     }
 
-    fn ask(&mut self, stdin: &mut Stdin) -> Result<(), io::Error> {
-        let (card, index) = self.get_card();
+    // When the current question ends, with the time limit of the question and of the session
+    #[inline]
+    fn deadline(&self, session_deadline: Option<Instant>) -> Option<Instant> {
+        match (self.time_limit.map(|limit| Instant::now() + limit), session_deadline) {
+            (Some(deadline), Some(session_deadline)) => Some(deadline.min(session_deadline)),
+            (deadline, session_deadline) => deadline.or(session_deadline),
+        }
+    }
 
-        let mut user_answer = String::new();
-        let mut user_tries = 1;
+    // Return false if the input was closed or the session is over
+    fn ask(&mut self, input: &Input, session_deadline: Option<Instant>) -> bool {
+        let question = match self.engine.next_question() {
+            Some(question) => question,
+            None => return false,
+        };
 
-        println!("{}", card.recto[0]);
+        println!("{}", question.prompt);
 
-        stdin.read_line(&mut user_answer)?;
+        let deadline = self.deadline(session_deadline);
 
         loop {
-            if card.test(&user_answer) {
-                println!();
-                self.failed.remove_value(index);
-                break;
-            } else if user_tries == self.tries.get() {
-                println!("Answer : {}\n", card.formated_verso());
-                self.failed.push(index).unwrap();
-                break;
-            } else {
-                println!("{}", card.tip);
+            let outcome = match input.read_line(deadline) {
+                Line::Answer(user_answer) if user_answer.trim() == GIVE_UP => self.engine.give_up(),
+                Line::Answer(user_answer) => self.engine.submit_answer(&user_answer),
+                Line::Timeout => {
+                    println!("Time is up !");
+                    if let Outcome::Failed { answer } = self.engine.give_up() {
+                        println!("Answer : {}\n", answer);
+                    }
+                    return session_deadline.is_none_or(|deadline| Instant::now() < deadline);
+                }
+                Line::Closed => return false,
+            };
 
-                user_answer = String::new();
-                stdin.read_line(&mut user_answer)?;
+            match outcome {
+                Outcome::Correct { left } => {
+                    if let Some(left) = left {
+                        println!("Card mastered, {} left.", left);
+                    }
+                    println!();
+                    break;
+                }
+                Outcome::Wrong { tip } => println!("Tip : {}.", tip),
+                Outcome::Failed { answer } => {
+                    println!("Answer : {}\n", answer);
+                    if self.copy && !self.copy_answer(input, question.id, &answer) {
+                        return false;
+                    }
+                    break;
+                }
+                Outcome::Finished => return false,
             }
+        }
+
+        true
+    }
 
-            user_tries += 1;
+    fn save_checkpoint(&self) -> Result<(), io::Error> {
+        if self.checkpoint {
+            if let Some(history) = self.engine.history() {
+                history.save()?;
+            }
+            session::save(self)?;
         }
 
         Ok(())
     }
+
+    // Ask the user to type the answer of a failed card, return false if the input was closed
+    fn copy_answer(&self, input: &Input, id: usize, answer: &str) -> bool {
+        println!("Type the answer to continue :");
+
+        loop {
+            let user_answer = match input.read_line(None) {
+                Line::Answer(user_answer) => user_answer,
+                Line::Timeout | Line::Closed => return false,
+            };
+
+            if self.engine.is_answer(id, &user_answer) || user_answer.trim() == GIVE_UP {
+                println!();
+                return true;
+            }
+            println!("Answer : {}", answer);
+        }
+    }
 }
 
-enum Stat {
-    New,
-    Failed,
+impl<R: Rng + Default> Asker<R> {
+    /// Load the checkpoint of the last interrupted session, if there is one.
+    pub fn resume() -> Result<Option<Self>, io::Error> {
+        let mut asker: Self = match session::load()? {
+            Some(asker) => asker,
+            None => return Ok(None),
+        };
+        asker.engine.set_history(History::load()?);
+        asker.checkpoint = true;
+
+        Ok(Some(asker))
+    }
 }
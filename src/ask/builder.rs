@@ -1,16 +1,30 @@
-use std::{fmt, num::NonZeroU32};
+use std::{fmt, num::NonZeroU32, time::Duration};
 
-use rand::Rng;
+use rand::{seq::SliceRandom, Rng};
 
-use crate::deck::Deck;
+use crate::{card::Card, deck::Deck, history::History, leitner::Leitner};
 
-use super::{nulos::Nulos, Asker, FlipMode, Stat};
+use super::{
+    Asker, Engine, FlipMode, HintMode, LeitnerScheduler, Matching, Scheduler, SchedulerKind, Tries,
+};
 
 pub struct AskerBuilder<R: Rng> {
     deck: Deck,
     max_cycle: NonZeroU32,
-    tries: NonZeroU32,
+    limit: Option<NonZeroU32>,
+    new_cards: Option<u32>,
+    mastery: Option<NonZeroU32>,
+    tries: Tries,
     flip_mode: FlipMode,
+    matching: Matching,
+    hint_mode: HintMode,
+    copy: bool,
+    time_limit: Option<Duration>,
+    session_time_limit: Option<Duration>,
+    history: Option<History>,
+    checkpoint: bool,
+    scheduler_kind: SchedulerKind,
+    scheduler: Option<Box<dyn Scheduler>>,
     rng: R,
 }
 
@@ -20,8 +34,20 @@ impl<R: Rng> AskerBuilder<R> {
         Self {
             deck,
             max_cycle: NonZeroU32::new(1).unwrap(),
-            tries: NonZeroU32::new(1).unwrap(),
+            limit: None,
+            new_cards: None,
+            mastery: None,
+            tries: Tries::default(),
             flip_mode: FlipMode::Recto,
+            matching: Matching::default(),
+            hint_mode: HintMode::default(),
+            copy: false,
+            time_limit: None,
+            session_time_limit: None,
+            history: None,
+            checkpoint: false,
+            scheduler_kind: SchedulerKind::default(),
+            scheduler: None,
             rng,
         }
     }
@@ -31,8 +57,27 @@ impl<R: Rng> AskerBuilder<R> {
         self.max_cycle = max_cycle;
     }
 
+    // Ask at most `limit` cards in the session, the due ones first
     #[inline]
-    pub fn tries(&mut self, tries: NonZeroU32) {
+    pub fn limit(&mut self, limit: NonZeroU32) {
+        self.limit = Some(limit);
+    }
+
+    // Ask at most `new_cards` cards never answered before, according to the history
+    #[inline]
+    pub fn new_cards(&mut self, new_cards: u32) {
+        self.new_cards = Some(new_cards);
+    }
+
+    // Ask the cards until each one is answered correctly `streak` times in a row,
+    // instead of a fixed number of passes
+    #[inline]
+    pub fn mastery(&mut self, streak: NonZeroU32) {
+        self.mastery = Some(streak);
+    }
+
+    #[inline]
+    pub fn tries(&mut self, tries: Tries) {
         self.tries = tries;
     }
 
@@ -41,26 +86,192 @@ impl<R: Rng> AskerBuilder<R> {
         self.flip_mode = verso_mode;
     }
 
+    #[inline]
+    pub fn matching(&mut self, matching: Matching) {
+        self.matching = matching;
+    }
+
+    #[inline]
+    pub fn hint_mode(&mut self, hint_mode: HintMode) {
+        self.hint_mode = hint_mode;
+    }
+
+    // Make the user type the answer of each failed card before the next one
+    #[inline]
+    pub fn copy(&mut self, copy: bool) {
+        self.copy = copy;
+    }
+
+    // Time to answer each question, after which the card is failed
+    #[inline]
+    pub fn time_limit(&mut self, time_limit: Duration) {
+        self.time_limit = Some(time_limit);
+    }
+
+    // Time after which the session ends
+    #[inline]
+    pub fn session_time_limit(&mut self, session_time_limit: Duration) {
+        self.session_time_limit = Some(session_time_limit);
+    }
+
+    // Record the answers into the history, saved at the end of the session
+    #[inline]
+    pub fn history(&mut self, history: History) {
+        self.history = Some(history);
+    }
+
+    // Save the session after each answer, so it can be resumed with `Asker::resume`,
+    // unless the scheduler can not be saved
+    #[inline]
+    pub fn checkpoint(&mut self, checkpoint: bool) {
+        self.checkpoint = checkpoint;
+    }
+
+    // Choose one of the built-in schedulers, using the passes and the flip mode
+    #[inline]
+    pub fn scheduler_kind(&mut self, scheduler_kind: SchedulerKind) {
+        self.scheduler_kind = scheduler_kind;
+    }
+
+    // Use a custom scheduler, replacing the scheduler kind
+    #[inline]
+    pub fn scheduler(&mut self, scheduler: Box<dyn Scheduler>) {
+        self.scheduler = Some(scheduler);
+    }
+
+    // Only ask the cards due in the Leitner boxes, and move them between boxes
+    // instead of asking the failed ones again
+    #[inline]
+    pub fn leitner(&mut self, leitner: Leitner) {
+        self.scheduler(Box::new(LeitnerScheduler::new(leitner)));
+    }
+
     #[inline]
     pub fn build(mut self) -> Asker<R> {
+        let copy = self.copy;
+        let time_limit = self.time_limit.take();
+        let session_time_limit = self.session_time_limit.take();
+        let checkpoint = self.checkpoint;
+        let engine = self.build_engine();
+
+        Asker {
+            // A custom scheduler may not be saved, the session being not resumable then
+            checkpoint: checkpoint && engine.can_checkpoint(),
+            engine,
+            copy,
+            time_limit,
+            session_time_limit,
+        }
+    }
+
+    /// Build the session without the terminal, ignoring the time limits, the
+    /// copy mode and the checkpoints.
+    pub fn build_engine(mut self) -> Engine<R> {
+        // With mastery, the cards are asked until they are all retired
+        let max_cycle = if self.mastery.is_some() {
+            None
+        } else {
+            Some(self.max_cycle)
+        };
+        let all_cases = self.flip_mode.is_all_cases();
+        let scheduler_kind = self.scheduler_kind;
+        let scheduler = self
+            .scheduler
+            .take()
+            .unwrap_or_else(|| scheduler_kind.scheduler(max_cycle, all_cases));
+
+        let mut origins = self.deck.orient();
         match self.flip_mode {
             FlipMode::Verso => self.deck.flip_all(),
-            FlipMode::Random(_) => self.deck.flip_random(&mut self.rng),
+            FlipMode::Random(random_flip) => self.deck.flip_random(&mut self.rng, random_flip),
             FlipMode::Recto => (),
         }
+        // The flip mode picks a direction of each card, the other one being
+        // added if it was already answered, to be asked when it is due
+        if let Some(history) = self
+            .history
+            .as_ref()
+            .filter(|_| scheduler.follows_schedule())
+        {
+            self.deck.add_turned(&mut origins, |card| {
+                !history
+                    .reviews(&card.key_of(card.direction().other()))
+                    .is_empty()
+            });
+        }
+
+        if self.limit.is_some() || self.new_cards.is_some() {
+            let ids = self.select_cards(&*scheduler, &origins);
+            self.deck.keep(&ids);
+        }
 
         self.deck.suffle(&mut self.rng);
 
-        Asker {
-            deck: self.deck,
-            failed: Nulos::new(),
-            all_cases: self.flip_mode.is_all_cases(),
-            cycle_counter: 0,
-            max_cycle: self.max_cycle,
-            tries: self.tries,
-            stat: Stat::New,
-            rng: self.rng,
+        Engine::new(
+            self.deck,
+            scheduler,
+            self.mastery,
+            self.tries,
+            self.matching,
+            self.hint_mode,
+            self.history,
+            self.rng,
+        )
+    }
+}
+
+impl<R: Rng> AskerBuilder<R> {
+    // Ids of the cards of the session : the due cards, then the new ones, then
+    // the other answered ones, each group being shuffled. The cards with the
+    // same origin, the directions of a card, are kept or left out together.
+    fn select_cards(&mut self, scheduler: &dyn Scheduler, origins: &[usize]) -> Vec<usize> {
+        let mut directions: Vec<Vec<usize>> = Vec::new();
+        for (id, origin) in origins.iter().enumerate() {
+            if *origin >= directions.len() {
+                directions.resize(origin + 1, Vec::new());
+            }
+            directions[*origin].push(id);
         }
+
+        let (mut due, mut new, mut seen) = (Vec::new(), Vec::new(), Vec::new());
+        let history = self.history.as_ref();
+        for ids in directions {
+            let cards = ids
+                .iter()
+                .map(|id| self.deck.get_card_by_index(*id).unwrap())
+                .collect::<Vec<_>>();
+            let is_new = |card: &&Card| {
+                history.is_none_or(|history| history.reviews(&card.key()).is_empty())
+            };
+
+            // A card is new if one of its directions was never answered
+            if cards
+                .iter()
+                .any(|card| !is_new(card) && scheduler.is_due(card, history))
+            {
+                due.push(ids);
+            } else if cards.iter().any(is_new) {
+                new.push(ids);
+            } else {
+                seen.push(ids);
+            }
+        }
+
+        for group in [&mut due, &mut new, &mut seen] {
+            group.shuffle(&mut self.rng);
+        }
+        if let Some(new_cards) = self.new_cards {
+            new.truncate(new_cards as usize);
+        }
+
+        let mut selected = due;
+        selected.append(&mut new);
+        selected.append(&mut seen);
+        if let Some(limit) = self.limit {
+            selected.truncate(limit.get() as usize);
+        }
+
+        selected.into_iter().flatten().collect()
     }
 }
 
@@ -69,8 +280,18 @@ impl<R: Rng> fmt::Debug for AskerBuilder<R> {
         f.debug_struct("AskerBuilder")
             .field("deck_len", &self.deck.len())
             .field("max_cycle", &self.max_cycle)
+            .field("limit", &self.limit)
+            .field("new_cards", &self.new_cards)
+            .field("mastery", &self.mastery)
             .field("tries", &self.tries)
             .field("flip_mode", &self.flip_mode)
+            .field("matching", &self.matching)
+            .field("hint_mode", &self.hint_mode)
+            .field("copy", &self.copy)
+            .field("time_limit", &self.time_limit)
+            .field("session_time_limit", &self.session_time_limit)
+            .field("checkpoint", &self.checkpoint)
+            .field("scheduler_kind", &self.scheduler_kind)
             .finish_non_exhaustive()
     }
 }
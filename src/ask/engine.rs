@@ -0,0 +1,405 @@
+use std::convert::TryFrom;
+use std::io;
+use std::num::NonZeroU32;
+use std::time::Instant;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    card::{Card, Direction},
+    deck::Deck,
+    history::{CardKeyMap, History},
+};
+
+use super::{scheduler, HintMode, Matching, Scheduler, SchedulerCheckpoint, Tries};
+
+/// The logic of a session without any input or output : get the question with
+/// `next_question`, answer it with `submit_answer` or `give_up`, until
+/// `next_question` returns `None`. Call `finish` at the end to save the
+/// history and the scheduler.
+#[derive(Serialize, Deserialize)]
+#[serde(
+    try_from = "EngineState",
+    bound(serialize = "", deserialize = "R: Rng + Default")
+)]
+pub struct Engine<R: Rng> {
+    deck: Deck,
+    #[serde(serialize_with = "scheduler::serialize")]
+    scheduler: Box<dyn Scheduler>,
+    // Id of the card being asked, `None` once the scheduler ended the session
+    current: Option<usize>,
+    started: bool,
+    // Tries already used on the current card
+    user_tries: u32,
+    // Correct answers in a row needed to retire a card, the session ending when all are retired
+    mastery: Option<NonZeroU32>,
+    // Correct answers in a row of each direction of the cards
+    streaks: CardKeyMap<u32>,
+    tries: Tries,
+    matching: Matching,
+    hint_mode: HintMode,
+    // When the current card was first asked, for the response time
+    #[serde(skip)]
+    asked_at: Option<Instant>,
+    #[serde(skip)]
+    history: Option<History>,
+    #[serde(skip)]
+    rng: R,
+}
+
+// Engine as deserialized, before checking it against its deck
+#[derive(Deserialize)]
+struct EngineState {
+    deck: Deck,
+    scheduler: SchedulerCheckpoint,
+    current: Option<usize>,
+    started: bool,
+    user_tries: u32,
+    mastery: Option<NonZeroU32>,
+    streaks: CardKeyMap<u32>,
+    tries: Tries,
+    matching: Matching,
+    hint_mode: HintMode,
+}
+
+impl<R: Rng + Default> TryFrom<EngineState> for Engine<R> {
+    type Error = String;
+
+    fn try_from(state: EngineState) -> Result<Self, Self::Error> {
+        if state
+            .current
+            .is_some_and(|current| current >= state.deck.len())
+        {
+            return Err("The current card is out of the deck.".to_owned());
+        }
+        if state.tries.is_last(state.user_tries) {
+            return Err("There is no try left for the current card.".to_owned());
+        }
+        state.scheduler.check(state.deck.len())?;
+
+        Ok(Self {
+            deck: state.deck,
+            scheduler: state.scheduler.into_scheduler(),
+            current: state.current,
+            started: state.started,
+            user_tries: state.user_tries,
+            mastery: state.mastery,
+            streaks: state.streaks,
+            tries: state.tries,
+            matching: state.matching,
+            hint_mode: state.hint_mode,
+            asked_at: None,
+            history: None,
+            rng: R::default(),
+        })
+    }
+}
+
+/// The current question and its metadata.
+#[derive(Debug, Clone, Serialize)]
+pub struct Question {
+    // Id of the card in the deck
+    pub id: usize,
+    pub prompt: String,
+    // Recto of the card in the history, with `direction` its key
+    pub key: String,
+    pub direction: Direction,
+    // Number of the try, from 1
+    pub try_number: u32,
+    // `None` if the tries are unlimited
+    pub tries_left: Option<u32>,
+}
+
+/// What happened after an answer.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Outcome {
+    // Cards left to master, if the answer retired the card
+    Correct { left: Option<usize> },
+    // Try again with a tip
+    Wrong { tip: String },
+    // No try left, or the user gave up
+    Failed { answer: String },
+    // There is no question to answer
+    Finished,
+}
+
+impl<R: Rng> Engine<R> {
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn new(
+        deck: Deck,
+        scheduler: Box<dyn Scheduler>,
+        mastery: Option<NonZeroU32>,
+        tries: Tries,
+        matching: Matching,
+        hint_mode: HintMode,
+        history: Option<History>,
+        rng: R,
+    ) -> Self {
+        Self {
+            streaks: CardKeyMap::default(),
+            deck,
+            scheduler,
+            current: None,
+            started: false,
+            user_tries: 0,
+            mastery,
+            tries,
+            matching,
+            hint_mode,
+            asked_at: None,
+            history,
+            rng,
+        }
+    }
+
+    /// The question to answer, or `None` if the session is over.
+    pub fn next_question(&mut self) -> Option<Question> {
+        if !self.started {
+            self.started = true;
+            self.advance();
+        }
+        if self.is_over() {
+            return None;
+        }
+        self.asked_at.get_or_insert_with(Instant::now);
+
+        let (card, id) = self.get_card();
+        Some(Question {
+            id,
+            prompt: card.recto[0].clone(),
+            key: card.key().recto,
+            direction: card.direction(),
+            try_number: self.user_tries + 1,
+            tries_left: match self.tries {
+                Tries::Limited(tries) => Some(tries.get() - self.user_tries),
+                Tries::Unlimited => None,
+            },
+        })
+    }
+
+    /// Answer the current question.
+    pub fn submit_answer(&mut self, answer: &str) -> Outcome {
+        if self.next_question().is_none() {
+            return Outcome::Finished;
+        }
+
+        self.user_tries += 1;
+        let card = self.get_card().0;
+
+        if card.test(answer, &self.matching) {
+            let left = self.answered(true);
+            Outcome::Correct { left }
+        } else if self.tries.is_last(self.user_tries) {
+            self.give_up()
+        } else {
+            let tip = match self.hint_mode {
+                HintMode::Tips => card.hint(self.user_tries as usize - 1),
+                HintMode::Cloze => card.cloze(self.user_tries as usize),
+            };
+            Outcome::Wrong { tip }
+        }
+    }
+
+    /// Fail the current question, when the user gives up or runs out of time.
+    pub fn give_up(&mut self) -> Outcome {
+        if self.next_question().is_none() {
+            return Outcome::Finished;
+        }
+
+        let answer = self.get_card().0.formated_verso();
+        self.answered(false);
+        Outcome::Failed { answer }
+    }
+
+    /// Return true if `answer` is an answer of the card `id`.
+    #[inline]
+    pub fn is_answer(&self, id: usize, answer: &str) -> bool {
+        self.deck
+            .get_card_by_index(id)
+            .is_some_and(|card| card.test(answer, &self.matching))
+    }
+
+    #[inline]
+    pub fn is_over(&self) -> bool {
+        self.current.is_none()
+            || (self.mastery.is_some()
+                && self.deck.cards().iter().all(|card| self.is_retired(card)))
+    }
+
+    #[inline]
+    pub const fn tries(&self) -> Tries {
+        self.tries
+    }
+
+    #[inline]
+    pub fn deck(&self) -> &Deck {
+        &self.deck
+    }
+
+    /// Save the history and the state of the scheduler.
+    pub fn finish(&mut self) -> Result<(), io::Error> {
+        if let Some(history) = &self.history {
+            history.save()?;
+        }
+        self.scheduler.finish()
+    }
+
+    // Return false if the scheduler can not be saved in a session checkpoint
+    #[inline]
+    pub(super) fn can_checkpoint(&self) -> bool {
+        self.scheduler.checkpoint().is_some()
+    }
+
+    #[inline]
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    #[inline]
+    pub(super) fn set_history(&mut self, history: History) {
+        self.history = Some(history);
+    }
+
+    #[inline]
+    fn advance(&mut self) {
+        self.current = self
+            .scheduler
+            .next_card(&mut self.deck, self.history.as_ref(), &mut self.rng);
+    }
+
+    // The current card and its id, only while the session is not over
+    #[inline]
+    fn get_card(&self) -> (&Card, usize) {
+        let index = self.current.unwrap();
+        (self.deck.get_card_by_index(index).unwrap(), index)
+    }
+
+    #[inline]
+    fn is_retired(&self, card: &Card) -> bool {
+        self.mastery.is_some_and(|mastery| {
+            self.streaks
+                .get(&card.key())
+                .is_some_and(|streak| *streak >= mastery.get())
+        })
+    }
+
+    // Update the scheduler, the streak and the history with the answer of the
+    // current card, then go to the next card. Return the number of cards left
+    // to master if the card was retired.
+    fn answered(&mut self, correct: bool) -> Option<usize> {
+        let response_time = self.asked_at.take().map(|at| at.elapsed()).unwrap_or_default();
+        let index = self.current.unwrap();
+        let card = self.deck.get_card_by_index(index).unwrap();
+        let key = card.key();
+
+        self.scheduler.answered(card, index, correct);
+        if let Some(history) = &mut self.history {
+            history.record(key.clone(), correct, response_time)
+        }
+
+        let streak = self.streaks.entry(key).or_default();
+        if correct {
+            *streak += 1;
+        } else {
+            *streak = 0;
+        }
+        let left = if self.is_retired(card) {
+            Some(
+                self.deck
+                    .cards()
+                    .iter()
+                    .filter(|card| !self.is_retired(card))
+                    .count(),
+            )
+        } else {
+            None
+        };
+
+        self.user_tries = 0;
+        self.advance();
+        while !self.is_over() && self.is_retired(self.get_card().0) {
+            self.advance();
+        }
+
+        left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Sides, Tip};
+    use crate::session::assert_round_trip;
+    use rand::rngs::ThreadRng;
+
+    fn engine() -> Engine<ThreadRng> {
+        let cards = ["one", "two"]
+            .iter()
+            .map(|recto| {
+                Card::new(
+                    vec![recto.to_string()],
+                    vec!["x".to_owned()],
+                    Tip::None,
+                    Sides::Any,
+                )
+            })
+            .collect();
+        Engine::new(
+            Deck::new(cards),
+            Box::new(super::super::Passes::new(None, false)),
+            None,
+            Tries::from_count(2),
+            Matching::default(),
+            HintMode::default(),
+            None,
+            rand::thread_rng(),
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut engine = engine();
+        let question = engine.next_question().unwrap();
+        engine.submit_answer("wrong");
+
+        let mut loaded = assert_round_trip(&engine);
+        let resumed = loaded.next_question().unwrap();
+        assert_eq!(resumed.id, question.id);
+        assert_eq!(resumed.try_number, 2);
+    }
+
+    #[test]
+    fn reject_invalid_state() {
+        let mut engine = engine();
+        engine.next_question();
+        let json = serde_json::to_value(&engine).unwrap();
+
+        for (field, value) in [
+            ("current", serde_json::json!(2)),
+            ("user_tries", serde_json::json!(2)),
+        ] {
+            let mut json = json.clone();
+            json[field] = value;
+            assert!(
+                serde_json::from_value::<Engine<ThreadRng>>(json).is_err(),
+                "{}",
+                field
+            );
+        }
+
+        let mut sequential = json.clone();
+        sequential["scheduler"] = serde_json::json!({"Sequential": {
+            "next": 7,
+            "all_cases": false,
+            "cycle_counter": 0,
+            "max_cycle": null,
+        }});
+        assert!(serde_json::from_value::<Engine<ThreadRng>>(sequential).is_err());
+
+        let mut json = json;
+        json["deck"]["cards"][0]["recto"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<Engine<ThreadRng>>(json).is_err());
+    }
+}
@@ -1,18 +1,122 @@
-type AllCases = bool;
+use std::convert::TryFrom;
 
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum FlipMode {
     Recto,
     Verso,
-    Random(AllCases),
+    Random(RandomFlip),
+}
+
+/// Settings of the random flip mode. With `all_cases`, every card is flipped
+/// at each new pass, so both directions are asked across two passes whatever
+/// the ratio.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "RandomFlipState")]
+pub struct RandomFlip {
+    // Part of the cards flipped, from 0 to 1
+    ratio: f64,
+    // Flip each card with a probability of `ratio` instead of exactly `ratio` of the cards
+    independent: bool,
+    all_cases: bool,
+}
+
+// Settings as deserialized, before checking the ratio
+#[derive(Deserialize)]
+struct RandomFlipState {
+    ratio: f64,
+    independent: bool,
+    all_cases: bool,
 }
 
 impl FlipMode {
     pub fn is_all_cases(&self) -> bool {
-        if let Self::Random(all_cases) = self {
-            *all_cases
+        if let Self::Random(random_flip) = self {
+            random_flip.is_all_cases()
         } else {
             false
         }
     }
 }
+
+impl RandomFlip {
+    // Part of the cards flipped, unless set otherwise
+    pub const DEFAULT_RATIO: f64 = 0.25;
+
+    /// Return an error if the ratio is not between 0 and 1.
+    pub fn new(ratio: f64, independent: bool, all_cases: bool) -> Result<Self, String> {
+        if !(0. ..=1.).contains(&ratio) {
+            return Err("The random ratio must be between 0 and 1".to_owned());
+        }
+
+        Ok(Self {
+            ratio,
+            independent,
+            all_cases,
+        })
+    }
+
+    #[inline]
+    pub const fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    #[inline]
+    pub const fn is_independent(&self) -> bool {
+        self.independent
+    }
+
+    #[inline]
+    pub const fn is_all_cases(&self) -> bool {
+        self.all_cases
+    }
+}
+
+impl TryFrom<RandomFlipState> for RandomFlip {
+    type Error = String;
+
+    #[inline]
+    fn try_from(state: RandomFlipState) -> Result<Self, Self::Error> {
+        Self::new(state.ratio, state.independent, state.all_cases)
+    }
+}
+
+impl Default for RandomFlip {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            ratio: Self::DEFAULT_RATIO,
+            independent: false,
+            all_cases: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::assert_round_trip;
+
+    #[test]
+    fn round_trip() {
+        let random_flip = RandomFlip::new(0.5, true, true).unwrap();
+        for flip_mode in [
+            FlipMode::Recto,
+            FlipMode::Verso,
+            FlipMode::Random(random_flip),
+        ] {
+            assert_round_trip(&flip_mode);
+        }
+        assert!(assert_round_trip(&FlipMode::Random(random_flip)).is_all_cases());
+    }
+
+    #[test]
+    fn reject_invalid_ratio() {
+        for ratio in [-0.1, 1.5, f64::NAN] {
+            assert!(RandomFlip::new(ratio, false, false).is_err());
+        }
+        let json = r#"{"Random": {"ratio": 1.5, "independent": false, "all_cases": false}}"#;
+        assert!(serde_json::from_str::<FlipMode>(json).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// What is shown after a wrong answer.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum HintMode {
+    // The hints of the card, or hints generated from the answer
+    #[default]
+    Tips,
+    // One more letter of the answer for each wrong answer
+    Cloze,
+}
@@ -0,0 +1,51 @@
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Instant;
+
+/// Lines typed by the user, read in another thread so a question can time out.
+pub struct Input {
+    receiver: Receiver<String>,
+}
+
+pub enum Line {
+    Answer(String),
+    Timeout,
+    Closed,
+}
+
+impl Input {
+    pub fn stdin() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let sent = line.map(|line| sender.send(line).is_ok());
+                if !matches!(sent, Ok(true)) {
+                    break;
+                }
+            }
+        });
+
+        Self { receiver }
+    }
+
+    // Wait for a line until the deadline, if any
+    pub fn read_line(&self, deadline: Option<Instant>) -> Line {
+        let line = match deadline {
+            Some(deadline) => self
+                .receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => self
+                .receiver
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match line {
+            Ok(line) => Line::Answer(line),
+            Err(RecvTimeoutError::Timeout) => Line::Timeout,
+            Err(RecvTimeoutError::Disconnected) => Line::Closed,
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::{self, Write};
+use std::time::Instant;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::input::{Input, Line};
+use super::{Asker, Outcome, Question};
+
+/// Event written by `Asker::run_machine`, as one JSON object per line.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    // Written again after each wrong answer, with the next try
+    Question(Question),
+    Feedback(Outcome),
+    // No time left to answer, the question being failed
+    TimeUp,
+    // The line read is not a message, or the cards can not be read
+    Error {
+        message: String,
+    },
+    // Written at the end, `over` being false if the session can be resumed
+    Summary {
+        answered: u32,
+        correct: u32,
+        failed: u32,
+        over: bool,
+    },
+}
+
+/// Message read by `Asker::run_machine`, as one JSON object per line.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    Answer {
+        answer: String,
+    },
+    #[serde(rename = "giveup")]
+    GiveUp,
+    // Stop the session, which can be resumed later
+    Quit,
+}
+
+impl<R: Rng> Asker<R> {
+    /// Run the session over the standard input and output with JSON lines,
+    /// for the programs wrapping pixo. The copy mode is ignored.
+    pub fn run_machine(mut self) -> Result<(), io::Error> {
+        let input = Input::stdin();
+        let session_deadline = self.session_time_limit.map(|limit| Instant::now() + limit);
+        let (mut correct, mut failed) = (0, 0);
+        let mut deadline = None;
+
+        while let Some(question) = self.engine.next_question() {
+            // The time limit is for all the tries of a question
+            if question.try_number == 1 {
+                self.save_checkpoint()?;
+                deadline = self.deadline(session_deadline);
+            }
+            Event::Question(question).emit()?;
+
+            let outcome = loop {
+                match input.read_line(deadline) {
+                    Line::Answer(line) => match serde_json::from_str(&line) {
+                        Ok(Message::Answer { answer }) => {
+                            break Some(self.engine.submit_answer(&answer))
+                        }
+                        Ok(Message::GiveUp) => break Some(self.engine.give_up()),
+                        Ok(Message::Quit) => break None,
+                        Err(error) => Event::Error {
+                            message: error.to_string(),
+                        }
+                        .emit()?,
+                    },
+                    Line::Timeout => {
+                        Event::TimeUp.emit()?;
+                        break Some(self.engine.give_up());
+                    }
+                    Line::Closed => break None,
+                }
+            };
+
+            match outcome {
+                Some(outcome) => {
+                    match outcome {
+                        Outcome::Correct { .. } => correct += 1,
+                        Outcome::Failed { .. } => failed += 1,
+                        Outcome::Wrong { .. } | Outcome::Finished => (),
+                    }
+                    Event::Feedback(outcome).emit()?;
+                }
+                None => break,
+            }
+
+            if session_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+        }
+
+        let over = self.end(session_deadline)?;
+        Event::Summary {
+            answered: correct + failed,
+            correct,
+            failed,
+            over,
+        }
+        .emit()
+    }
+}
+
+impl Event {
+    /// Write the event as a JSON line on the standard output.
+    pub fn emit(&self) -> Result<(), io::Error> {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer(&mut stdout, self)?;
+        writeln!(stdout)?;
+        stdout.flush()
+    }
+}
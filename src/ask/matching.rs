@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// How an answer is compared to the verso of a card.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Matching {
+    pub ignore_case: bool,
+    pub ignore_punctuation: bool,
+}
+
+impl Matching {
+    pub fn normalize(&self, answer: &str) -> String {
+        let mut answer = answer.trim().to_owned();
+
+        if self.ignore_case {
+            answer = answer.to_lowercase();
+        }
+        if self.ignore_punctuation {
+            answer = answer
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+
+        answer
+    }
+
+    #[inline]
+    pub fn is_match(&self, answer: &str, true_answer: &str) -> bool {
+        self.normalize(answer) == self.normalize(true_answer)
+    }
+}
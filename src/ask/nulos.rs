@@ -1,6 +1,7 @@
 use rand::{prelude::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Nulos {
     failed1: Vec<usize>,
     failed2: Vec<usize>,
@@ -25,6 +26,15 @@ impl Nulos {
         self.failed1.is_empty() && self.failed2.is_empty()
     }
 
+    // Return true if all the values are lower than `len`
+    #[inline]
+    pub fn is_below(&self, len: usize) -> bool {
+        self.failed1
+            .iter()
+            .chain(&self.failed2)
+            .all(|value| *value < len)
+    }
+
     #[inline]
     pub fn shuffle<R: Rng>(&mut self, rng: &mut R) {
         self.failed1.shuffle(rng);
@@ -101,7 +111,7 @@ impl Nulos {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 enum Failed {
     One,
     Two,
@@ -121,3 +131,26 @@ impl Failed {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::assert_round_trip;
+
+    #[test]
+    fn round_trip() {
+        let mut rng = rand::thread_rng();
+        let mut nulos = Nulos::new();
+        nulos.push(3);
+        nulos.push(1);
+        nulos.advance(&mut rng);
+        nulos.push(4);
+        nulos.remove_value(1);
+
+        let mut loaded = assert_round_trip(&nulos);
+        assert_eq!(loaded.get(), nulos.get());
+        loaded.advance(&mut rng);
+        nulos.advance(&mut rng);
+        assert_eq!(loaded.get(), nulos.get());
+    }
+}
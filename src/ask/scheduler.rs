@@ -0,0 +1,171 @@
+mod leitner;
+mod passes;
+mod random;
+mod sequential;
+mod spaced;
+mod weighted;
+
+use std::io;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use rand::RngCore;
+use serde::{ser::Error, Deserialize, Serialize, Serializer};
+
+use crate::{
+    card::Card,
+    deck::Deck,
+    history::{self, History},
+};
+
+pub use self::leitner::LeitnerScheduler;
+pub use passes::Passes;
+pub use random::WithReplacement;
+pub use sequential::Sequential;
+pub use spaced::SpacedRepetition;
+pub use weighted::Weighted;
+
+/// Decide which card of the deck is asked next.
+///
+/// `Asker` calls `next_card` before each question and `answered` after each
+/// answer, the session ending when `next_card` returns `None`.
+pub trait Scheduler {
+    /// Id of the next card to ask, or `None` to end the session.
+    fn next_card(
+        &mut self,
+        deck: &mut Deck,
+        history: Option<&History>,
+        rng: &mut dyn RngCore,
+    ) -> Option<usize>;
+
+    fn answered(&mut self, _card: &Card, _id: usize, _correct: bool) {}
+
+    // Return true if the card should be reviewed first when the session is limited
+    fn is_due(&self, card: &Card, history: Option<&History>) -> bool {
+        history.is_none_or(|history| spaced::is_due(history.reviews(&card.key()), history::now()))
+    }
+
+    // Return true if only the due cards are asked, each direction of a card
+    // being then asked when it is due
+    fn follows_schedule(&self) -> bool {
+        false
+    }
+
+    // State saved in the session checkpoint, `None` if the session can not be resumed
+    fn checkpoint(&self) -> Option<SchedulerCheckpoint> {
+        None
+    }
+
+    // Save the state of the scheduler at the end of the session
+    fn finish(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
+
+/// The built-in schedulers, without their settings.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchedulerKind {
+    // Passes over the shuffled deck, the failed cards being asked again after each one
+    #[default]
+    Passes,
+    // Passes over the deck in the order of the data file
+    Sequential,
+    // Cards drawn at random, a card being able to come back at any time
+    Random,
+    // Cards drawn at random, the often and recently failed ones being drawn more often
+    Weighted,
+    // Only the cards due according to the history, at growing intervals
+    Spaced,
+}
+
+impl SchedulerKind {
+    pub const NAMES: [&'static str; 5] = ["passes", "sequential", "random", "weighted", "spaced"];
+
+    // Only the schedulers going through the whole deck at each pass can ask
+    // every card in both directions
+    #[inline]
+    pub const fn supports_all_cases(self) -> bool {
+        matches!(self, Self::Passes | Self::Sequential)
+    }
+
+    // `max_cycle` is the number of passes, `None` for no limit, `all_cases` being
+    // ignored if it is not supported
+    pub fn scheduler(self, max_cycle: Option<NonZeroU32>, all_cases: bool) -> Box<dyn Scheduler> {
+        match self {
+            Self::Passes => Box::new(Passes::new(max_cycle, all_cases)),
+            Self::Sequential => Box::new(Sequential::new(max_cycle, all_cases)),
+            Self::Random => Box::new(WithReplacement::new(max_cycle)),
+            Self::Weighted => Box::new(Weighted::new(max_cycle)),
+            Self::Spaced => Box::new(SpacedRepetition::new()),
+        }
+    }
+}
+
+impl FromStr for SchedulerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "passes" => Ok(Self::Passes),
+            "sequential" => Ok(Self::Sequential),
+            "random" => Ok(Self::Random),
+            "weighted" => Ok(Self::Weighted),
+            "spaced" => Ok(Self::Spaced),
+            _ => Err(format!("Unknown scheduler {:?}.", s)),
+        }
+    }
+}
+
+/// State of a built-in scheduler, saved in the session checkpoint.
+#[derive(Serialize, Deserialize)]
+pub enum SchedulerCheckpoint {
+    Passes(Passes),
+    Sequential(Sequential),
+    Random(WithReplacement),
+    Weighted(Weighted),
+    Spaced(SpacedRepetition),
+    Leitner(LeitnerScheduler),
+}
+
+impl SchedulerCheckpoint {
+    // Check that the cards of the state are in a deck of `len` cards
+    pub fn check(&self, len: usize) -> Result<(), String> {
+        let fits = match self {
+            Self::Passes(scheduler) => scheduler.fits(len),
+            Self::Sequential(scheduler) => scheduler.fits(len),
+            Self::Random(_) | Self::Weighted(_) => true,
+            Self::Spaced(scheduler) => scheduler.fits(len),
+            Self::Leitner(scheduler) => scheduler.fits(len),
+        };
+
+        if fits {
+            Ok(())
+        } else {
+            Err("The scheduler has a card out of the deck.".to_owned())
+        }
+    }
+
+    pub fn into_scheduler(self) -> Box<dyn Scheduler> {
+        match self {
+            Self::Passes(scheduler) => Box::new(scheduler),
+            Self::Sequential(scheduler) => Box::new(scheduler),
+            Self::Random(scheduler) => Box::new(scheduler),
+            Self::Weighted(scheduler) => Box::new(scheduler),
+            Self::Spaced(scheduler) => Box::new(scheduler),
+            Self::Leitner(scheduler) => Box::new(scheduler.resume()),
+        }
+    }
+}
+
+// Serialize the scheduler of `Asker` through its checkpoint
+#[allow(clippy::borrowed_box)]
+pub fn serialize<S: Serializer>(
+    scheduler: &Box<dyn Scheduler>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match scheduler.checkpoint() {
+        Some(checkpoint) => checkpoint.serialize(serializer),
+        None => Err(S::Error::custom("The scheduler can not be saved.")),
+    }
+}
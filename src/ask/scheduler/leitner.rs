@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+use std::io;
+use std::num::NonZeroU32;
+
+use rand::{seq::SliceRandom, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{card::Card, deck::Deck, history::History, leitner::Leitner};
+
+use super::{Scheduler, SchedulerCheckpoint};
+
+/// Ask once the cards due in their Leitner box, and move them between boxes
+/// instead of asking the failed ones again.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LeitnerScheduler {
+    leitner: Leitner,
+    // Not saved with the boxes
+    box_count: NonZeroU32,
+    queue: VecDeque<usize>,
+    started: bool,
+}
+
+impl LeitnerScheduler {
+    #[inline]
+    pub fn new(leitner: Leitner) -> Self {
+        Self {
+            box_count: leitner.box_count(),
+            leitner,
+            queue: VecDeque::new(),
+            started: false,
+        }
+    }
+
+    // Restore the Leitner boxes of a session checkpoint
+    #[inline]
+    pub fn resume(mut self) -> Self {
+        self.leitner.resume(self.box_count);
+        self
+    }
+
+    // Return true if the queued cards are in a deck of `len` cards
+    #[inline]
+    pub fn fits(&self, len: usize) -> bool {
+        self.queue.iter().all(|id| *id < len)
+    }
+}
+
+impl Scheduler for LeitnerScheduler {
+    fn next_card(
+        &mut self,
+        deck: &mut Deck,
+        _history: Option<&History>,
+        mut rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        if !self.started {
+            self.started = true;
+
+            let mut due = Vec::new();
+            for (id, card) in deck.cards().iter().enumerate() {
+                let key = card.key();
+                if self.leitner.is_due(&key) {
+                    due.push(id);
+                } else {
+                    self.leitner.skip(&key);
+                }
+            }
+            due.shuffle(&mut rng);
+            self.queue = due.into();
+        }
+
+        self.queue.pop_front()
+    }
+
+    fn answered(&mut self, card: &Card, _id: usize, correct: bool) {
+        // Failed cards wait in the first box instead of being asked again
+        if correct {
+            self.leitner.promote(card.key());
+        } else {
+            self.leitner.demote(card.key());
+        }
+    }
+
+    #[inline]
+    fn is_due(&self, card: &Card, _history: Option<&History>) -> bool {
+        self.leitner.is_due(&card.key())
+    }
+
+    #[inline]
+    fn follows_schedule(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn checkpoint(&self) -> Option<SchedulerCheckpoint> {
+        Some(SchedulerCheckpoint::Leitner(self.clone()))
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), io::Error> {
+        self.leitner.save()
+    }
+}
@@ -0,0 +1,114 @@
+use std::num::NonZeroU32;
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::{ask::Ask, card::Card, deck::Deck, history::History};
+
+use super::super::nulos::Nulos;
+use super::{Scheduler, SchedulerCheckpoint};
+
+/// Ask every card of the shuffled deck, then the failed ones until they are
+/// all answered, and start again for each pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Passes {
+    failed: Nulos,
+    all_cases: bool,
+    cycle_counter: u32,
+    max_cycle: Option<NonZeroU32>,
+    stat: Stat,
+}
+
+impl Passes {
+    #[inline]
+    pub const fn new(max_cycle: Option<NonZeroU32>, all_cases: bool) -> Self {
+        Self {
+            failed: Nulos::new(),
+            all_cases,
+            cycle_counter: 0,
+            max_cycle,
+            stat: Stat::Start,
+        }
+    }
+
+    // Return true if the failed cards are in a deck of `len` cards
+    #[inline]
+    pub fn fits(&self, len: usize) -> bool {
+        self.failed.is_below(len)
+    }
+
+    fn advance(&mut self, deck: &mut Deck, mut rng: &mut dyn RngCore) {
+        if matches!(self.stat, Stat::New) {
+            if deck.question_index() + 1 == deck.len() {
+                if self.failed.is_empty() {
+                    self.cycle_counter += 1;
+                    deck.suffle(&mut rng);
+                    if self.all_cases {
+                        deck.flip_all();
+                    }
+                    deck.advance();
+                } else {
+                    self.stat = Stat::Failed;
+                    self.failed.advance(&mut rng);
+                }
+            } else {
+                deck.advance()
+            }
+        } else if self.failed.is_empty() {
+            self.stat = Stat::New;
+            self.advance(deck, rng);
+        } else {
+            self.failed.advance(&mut rng);
+        }
+    }
+}
+
+impl Scheduler for Passes {
+    fn next_card(
+        &mut self,
+        deck: &mut Deck,
+        _history: Option<&History>,
+        rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        if deck.is_empty() {
+            return None;
+        }
+
+        match self.stat {
+            Stat::Start => self.stat = Stat::New,
+            _ => self.advance(deck, rng),
+        }
+
+        if self
+            .max_cycle
+            .is_some_and(|max_cycle| self.cycle_counter >= max_cycle.get())
+        {
+            return None;
+        }
+
+        match self.stat {
+            Stat::Failed => self.failed.get(),
+            _ => Some(deck.get_card().1),
+        }
+    }
+
+    fn answered(&mut self, _card: &Card, id: usize, correct: bool) {
+        if correct {
+            self.failed.remove_value(id);
+        } else {
+            self.failed.push(id).unwrap();
+        }
+    }
+
+    #[inline]
+    fn checkpoint(&self) -> Option<SchedulerCheckpoint> {
+        Some(SchedulerCheckpoint::Passes(self.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum Stat {
+    Start,
+    New,
+    Failed,
+}
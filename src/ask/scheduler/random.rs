@@ -0,0 +1,53 @@
+use std::num::NonZeroU32;
+
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{deck::Deck, history::History};
+
+use super::{Scheduler, SchedulerCheckpoint};
+
+/// Draw each card at random among the whole deck, as many times as there are
+/// cards for each pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithReplacement {
+    asked: usize,
+    max_cycle: Option<NonZeroU32>,
+}
+
+impl WithReplacement {
+    #[inline]
+    pub const fn new(max_cycle: Option<NonZeroU32>) -> Self {
+        Self {
+            asked: 0,
+            max_cycle,
+        }
+    }
+}
+
+impl Scheduler for WithReplacement {
+    fn next_card(
+        &mut self,
+        deck: &mut Deck,
+        _history: Option<&History>,
+        rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        if deck.is_empty() || is_session_over(self.asked, deck.len(), self.max_cycle) {
+            return None;
+        }
+
+        self.asked += 1;
+        Some(rng.gen_range(0..deck.len()))
+    }
+
+    #[inline]
+    fn checkpoint(&self) -> Option<SchedulerCheckpoint> {
+        Some(SchedulerCheckpoint::Random(self.clone()))
+    }
+}
+
+// Return true if as many cards as `max_cycle` passes over the deck were asked
+#[inline]
+pub(super) fn is_session_over(asked: usize, len: usize, max_cycle: Option<NonZeroU32>) -> bool {
+    max_cycle.is_some_and(|max_cycle| asked >= len * max_cycle.get() as usize)
+}
@@ -0,0 +1,71 @@
+use std::num::NonZeroU32;
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::{deck::Deck, history::History};
+
+use super::{Scheduler, SchedulerCheckpoint};
+
+/// Ask the cards in the order of the data file, once per pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sequential {
+    next: usize,
+    all_cases: bool,
+    cycle_counter: u32,
+    max_cycle: Option<NonZeroU32>,
+}
+
+impl Sequential {
+    #[inline]
+    pub const fn new(max_cycle: Option<NonZeroU32>, all_cases: bool) -> Self {
+        Self {
+            next: 0,
+            all_cases,
+            cycle_counter: 0,
+            max_cycle,
+        }
+    }
+
+    // Return true if the next card is in a deck of `len` cards, or just after it
+    #[inline]
+    pub const fn fits(&self, len: usize) -> bool {
+        self.next <= len
+    }
+}
+
+impl Scheduler for Sequential {
+    fn next_card(
+        &mut self,
+        deck: &mut Deck,
+        _history: Option<&History>,
+        _rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        if deck.is_empty() {
+            return None;
+        }
+
+        if self.next == deck.len() {
+            self.next = 0;
+            self.cycle_counter += 1;
+            if self.all_cases {
+                deck.flip_all();
+            }
+        }
+
+        if self
+            .max_cycle
+            .is_some_and(|max_cycle| self.cycle_counter >= max_cycle.get())
+        {
+            return None;
+        }
+
+        self.next += 1;
+        Some(self.next - 1)
+    }
+
+    #[inline]
+    fn checkpoint(&self) -> Option<SchedulerCheckpoint> {
+        Some(SchedulerCheckpoint::Sequential(self.clone()))
+    }
+}
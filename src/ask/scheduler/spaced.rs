@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+
+use rand::{seq::SliceRandom, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    card::Card,
+    deck::Deck,
+    history::{self, History, Review},
+};
+
+use super::{Scheduler, SchedulerCheckpoint};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Only ask the cards due according to the history, a card answered correctly
+/// `n` times in a row being due `2^(n-1)` days after its last review. The
+/// failed cards are asked again until they are answered.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SpacedRepetition {
+    queue: VecDeque<usize>,
+    started: bool,
+}
+
+impl SpacedRepetition {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Return true if the queued cards are in a deck of `len` cards
+    #[inline]
+    pub fn fits(&self, len: usize) -> bool {
+        self.queue.iter().all(|id| *id < len)
+    }
+}
+
+impl Scheduler for SpacedRepetition {
+    fn next_card(
+        &mut self,
+        deck: &mut Deck,
+        history: Option<&History>,
+        mut rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        if !self.started {
+            self.started = true;
+
+            let now = history::now();
+            let mut due = (0..deck.len())
+                .filter(|id| {
+                    let card = deck.get_card_by_index(*id).unwrap();
+                    history.is_none_or(|history| is_due(history.reviews(&card.key()), now))
+                })
+                .collect::<Vec<_>>();
+            due.shuffle(&mut rng);
+            self.queue = due.into();
+        }
+
+        self.queue.pop_front()
+    }
+
+    fn answered(&mut self, _card: &Card, id: usize, correct: bool) {
+        if !correct {
+            self.queue.push_back(id);
+        }
+    }
+
+    #[inline]
+    fn follows_schedule(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn checkpoint(&self) -> Option<SchedulerCheckpoint> {
+        Some(SchedulerCheckpoint::Spaced(self.clone()))
+    }
+}
+
+// Return true if the card has never been answered or its interval is over
+pub fn is_due(reviews: &[Review], now: u64) -> bool {
+    let last = match reviews.last() {
+        Some(last) => last,
+        None => return true,
+    };
+
+    let streak = reviews
+        .iter()
+        .rev()
+        .take_while(|review| review.correct)
+        .count() as u32;
+    let interval = match streak {
+        0 => 0,
+        streak => DAY << (streak - 1).min(32),
+    };
+
+    last.time.saturating_add(interval) <= now
+}
@@ -0,0 +1,103 @@
+use std::num::NonZeroU32;
+
+use rand::{distributions::WeightedIndex, prelude::Distribution, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    card::Card,
+    deck::Deck,
+    history::{self, CardKeyMap, History},
+};
+
+use super::{random::is_session_over, Scheduler, SchedulerCheckpoint};
+
+// Days after which the weight added by a failure is halved
+const FAILURE_HALF_LIFE: f64 = 3.;
+const DAY: f64 = 24. * 60. * 60.;
+
+/// Draw each card at random, with a probability proportional to its failure
+/// rate across the history and the current session, a recent failure making
+/// a card up to twice as likely to be drawn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Weighted {
+    asked: usize,
+    max_cycle: Option<NonZeroU32>,
+    // (failures, answers) of the current session, for each direction of the cards
+    session: CardKeyMap<(u32, u32)>,
+}
+
+impl Weighted {
+    #[inline]
+    pub fn new(max_cycle: Option<NonZeroU32>) -> Self {
+        Self {
+            asked: 0,
+            max_cycle,
+            session: CardKeyMap::default(),
+        }
+    }
+
+    // Failure rate of a card, a card never answered counting as failed once in two
+    // answers, multiplied by the recency of its last failure
+    fn weight(&self, card: &Card, history: Option<&History>, now: u64) -> f64 {
+        let key = card.key();
+        let (mut failures, mut answers) = self.session.get(&key).copied().unwrap_or_default();
+        // Seconds since the last failure
+        let mut last_failure = if failures != 0 { Some(0) } else { None };
+
+        if let Some(history) = history {
+            let reviews = history.reviews(&key);
+            failures += reviews.iter().filter(|review| !review.correct).count() as u32;
+            answers += reviews.len() as u32;
+            last_failure = last_failure.or_else(|| {
+                reviews
+                    .iter()
+                    .rev()
+                    .find(|review| !review.correct)
+                    .map(|review| now.saturating_sub(review.time))
+            });
+        }
+
+        let failure_rate = (failures + 1) as f64 / (answers + 2) as f64;
+        let recency = last_failure
+            .map(|seconds| 0.5f64.powf(seconds as f64 / DAY / FAILURE_HALF_LIFE))
+            .unwrap_or(0.);
+
+        failure_rate * (1. + recency)
+    }
+}
+
+impl Scheduler for Weighted {
+    fn next_card(
+        &mut self,
+        deck: &mut Deck,
+        history: Option<&History>,
+        mut rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        if deck.is_empty() || is_session_over(self.asked, deck.len(), self.max_cycle) {
+            return None;
+        }
+
+        let now = history::now();
+        let weights = deck
+            .cards()
+            .iter()
+            .map(|card| self.weight(card, history, now));
+        let distribution = WeightedIndex::new(weights).ok()?;
+
+        self.asked += 1;
+        Some(distribution.sample(&mut rng))
+    }
+
+    fn answered(&mut self, card: &Card, _id: usize, correct: bool) {
+        let (failures, answers) = self.session.entry(card.key()).or_default();
+        if !correct {
+            *failures += 1;
+        }
+        *answers += 1;
+    }
+
+    #[inline]
+    fn checkpoint(&self) -> Option<SchedulerCheckpoint> {
+        Some(SchedulerCheckpoint::Weighted(self.clone()))
+    }
+}
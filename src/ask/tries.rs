@@ -0,0 +1,33 @@
+use std::num::NonZeroU32;
+
+use serde::{Deserialize, Serialize};
+
+/// Number of tries the user has to answer a question.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Tries {
+    Limited(NonZeroU32),
+    // Ask again until the answer is correct or the user gives up
+    Unlimited,
+}
+
+impl Tries {
+    // 0 means an unlimited number of tries
+    #[inline]
+    pub fn from_count(count: u32) -> Self {
+        NonZeroU32::new(count).map_or(Self::Unlimited, Self::Limited)
+    }
+
+    #[inline]
+    pub const fn is_last(&self, user_tries: u32) -> bool {
+        match self {
+            Self::Limited(tries) => user_tries >= tries.get(),
+            Self::Unlimited => false,
+        }
+    }
+}
+
+impl Default for Tries {
+    fn default() -> Self {
+        Self::Limited(NonZeroU32::new(1).unwrap())
+    }
+}
@@ -1,43 +1,172 @@
-use std::{fmt, mem};
+use std::convert::TryFrom;
+use std::mem;
 
-use crate::ask::Ask;
+use serde::{Deserialize, Serialize};
 
+use crate::ask::{Ask, Matching};
+use crate::history::CardKey;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "CardState")]
 pub struct Card {
     pub recto: Vec<String>,
     pub verso: Vec<String>,
     pub tip: Tip,
-    pub only_recto: bool,
+    #[serde(default)]
+    pub sides: Sides,
+    #[serde(default)]
+    flipped: bool,
+}
+
+/// Directions in which a card is asked, set by its tags or by the default of its deck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sides {
+    // As the flip mode of the session chooses
+    #[default]
+    Any,
+    OnlyRecto,
+    OnlyVerso,
+    // Both directions are asked in each session
+    BothRequired,
+}
+
+impl Sides {
+    #[inline]
+    pub fn is_any(&self) -> bool {
+        *self == Self::Any
+    }
+}
+
+/// Side of a card asked, each direction being reviewed on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    // The recto is asked, the verso being the answer
+    Forward,
+    Reverse,
+}
+
+impl Direction {
+    #[inline]
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Forward => Self::Reverse,
+            Self::Reverse => Self::Forward,
+        }
+    }
 }
 
 impl Card {
-    pub const fn new(recto: Vec<String>, verso: Vec<String>, tip: Tip, only_recto: bool) -> Self {
+    pub const fn new(recto: Vec<String>, verso: Vec<String>, tip: Tip, sides: Sides) -> Self {
         Self {
             recto,
             verso,
             tip,
-            only_recto,
+            sides,
+            flipped: false,
         }
     }
 
+    // Only the cards asked in any direction are flipped by the flip mode
     #[inline]
     pub fn flip(&mut self) {
-        if !self.only_recto {
-            mem::swap(&mut self.recto, &mut self.verso);
-            self.tip.flip()
+        if self.sides.is_any() {
+            self.turn();
+        }
+    }
+
+    #[inline]
+    pub(crate) fn turn(&mut self) {
+        mem::swap(&mut self.recto, &mut self.verso);
+        self.tip.flip();
+        self.flipped = !self.flipped
+    }
+
+    #[inline]
+    pub const fn direction(&self) -> Direction {
+        if self.flipped {
+            Direction::Reverse
+        } else {
+            Direction::Forward
         }
     }
 
+    // Key of the asked direction of the card in the history
+    #[inline]
+    pub fn key(&self) -> CardKey {
+        self.key_of(self.direction())
+    }
+
+    #[inline]
+    pub fn key_of(&self, direction: Direction) -> CardKey {
+        let recto = if self.flipped {
+            &self.verso
+        } else {
+            &self.recto
+        };
+        CardKey::new(recto, direction)
+    }
+
     #[inline]
     pub fn formated_verso(&self) -> String {
         self.verso.join(" OR ")
     }
 
+    // Hint shown after the `nth` wrong answer, generated from the verso if the card has no tip
+    pub fn hint(&self, nth: usize) -> String {
+        let hints = self.tip.recto();
+        if hints.is_empty() {
+            generated_hint(&self.verso[0], nth)
+        } else {
+            hints[nth.min(hints.len() - 1)].clone()
+        }
+    }
+
+    // Verso with only its `revealed` first letters shown
+    #[inline]
+    pub fn cloze(&self, revealed: usize) -> String {
+        let mut count = 0;
+        mask(&self.verso[0], |_, _| {
+            count += 1;
+            count <= revealed
+        })
+    }
+
     // Return true if the answer was correct
     #[inline]
-    pub fn test(&self, answer: &str) -> bool {
+    pub fn test(&self, answer: &str, matching: &Matching) -> bool {
         self.verso
             .iter()
-            .any(|true_answer| true_answer.trim() == answer.trim())
+            .any(|true_answer| matching.is_match(answer, true_answer))
+    }
+}
+
+// Card as deserialized, before checking its sides
+#[derive(Deserialize)]
+struct CardState {
+    recto: Vec<String>,
+    verso: Vec<String>,
+    tip: Tip,
+    #[serde(default)]
+    sides: Sides,
+    #[serde(default)]
+    flipped: bool,
+}
+
+impl TryFrom<CardState> for Card {
+    type Error = String;
+
+    fn try_from(state: CardState) -> Result<Self, Self::Error> {
+        validate_sides(&state.recto, &state.verso)?;
+
+        Ok(Self {
+            recto: state.recto,
+            verso: state.verso,
+            tip: state.tip,
+            sides: state.sides,
+            flipped: state.flipped,
+        })
     }
 }
 
@@ -47,13 +176,39 @@ impl Ask for Card {
     }
 }
 
+// Hints revealed one per wrong answer, in order
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Tip {
     None,
-    One(String),
-    RectoVerso(String, String),
+    // Same hints for the recto and the verso
+    One(Vec<String>),
+    RectoVerso(Vec<String>, Vec<String>),
 }
 
 impl Tip {
+    #[inline]
+    pub const fn is_none(&self) -> bool {
+        matches!(self, Self::None)
+    }
+
+    // Hints shown when the recto is asked
+    #[inline]
+    pub fn recto(&self) -> &[String] {
+        match self {
+            Self::None => &[],
+            Self::One(hints) | Self::RectoVerso(hints, _) => hints,
+        }
+    }
+
+    // Hints shown when the verso is asked
+    #[inline]
+    pub fn verso(&self) -> &[String] {
+        match self {
+            Self::None => &[],
+            Self::One(hints) | Self::RectoVerso(_, hints) => hints,
+        }
+    }
+
     #[inline]
     fn flip(&mut self) {
         if let Self::RectoVerso(a, b) = self {
@@ -62,12 +217,100 @@ impl Tip {
     }
 }
 
-impl fmt::Display for Tip {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::None => f.write_str("Wrong answer."),
-            Self::One(tip) => f.write_fmt(format_args!("Tip : {}.", tip)),
-            Self::RectoVerso(tip, _) => f.write_fmt(format_args!("Tip : {}.", tip)),
+// Check that the card can be asked in both ways
+pub(crate) fn validate_sides(recto: &[String], verso: &[String]) -> Result<(), String> {
+    if recto.is_empty() {
+        Err(String::from("The recto is empty"))
+    } else if verso.is_empty() {
+        Err(String::from("The verso is empty"))
+    } else if recto
+        .iter()
+        .chain(verso.iter())
+        .any(|side| side.trim().is_empty())
+    {
+        Err(String::from("A recto or a verso is blank"))
+    } else {
+        Ok(())
+    }
+}
+
+// Hints generated from the answer : its first letter, its length, then its vowels
+fn generated_hint(answer: &str, nth: usize) -> String {
+    match nth {
+        0 => match answer.chars().find(|c| c.is_alphanumeric()) {
+            Some(first) => format!("Starts with {}", first),
+            None => String::from("Wrong answer"),
+        },
+        1 => mask(answer, |i, _| i == 0),
+        _ => mask(answer, |i, c| i == 0 || "aeiouyAEIOUYàâäéèêëîïôöùûüÿ".contains(c)),
+    }
+}
+
+// Hide the letters behind `_`, except those for which `reveal` (given their index in the
+// word) returns true
+fn mask<F: FnMut(usize, char) -> bool>(answer: &str, mut reveal: F) -> String {
+    answer
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    if !c.is_alphanumeric() || reveal(i, c) {
+                        c.to_string()
+                    } else {
+                        String::from("_")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("   ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::assert_round_trip;
+
+    fn card(tip: Tip) -> Card {
+        Card::new(
+            vec!["Hello".to_owned(), "Hi".to_owned()],
+            vec!["Coucou".to_owned()],
+            tip,
+            Sides::Any,
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        for tip in [
+            Tip::None,
+            Tip::One(vec!["Greeting".to_owned()]),
+            Tip::RectoVerso(
+                vec!["Greeting".to_owned()],
+                vec!["Salut".to_owned(), "C".to_owned()],
+            ),
+        ] {
+            assert_round_trip(&tip);
+
+            let mut card = card(tip);
+            assert_round_trip(&card);
+            card.flip();
+            let loaded = assert_round_trip(&card);
+            assert_eq!(loaded.direction(), Direction::Reverse);
+            assert_eq!(loaded.recto, ["Coucou"]);
+        }
+    }
+
+    #[test]
+    fn reject_invalid_sides() {
+        for json in [
+            r#"{"recto": [], "verso": ["Coucou"], "tip": "None"}"#,
+            r#"{"recto": ["Hello"], "verso": [], "tip": "None"}"#,
+            r#"{"recto": ["Hello", " "], "verso": ["Coucou"], "tip": "None"}"#,
+        ] {
+            assert!(serde_json::from_str::<Card>(json).is_err(), "{}", json);
         }
     }
 }
@@ -0,0 +1,438 @@
+use std::convert;
+use std::ffi::OsString;
+use std::path::Path;
+
+use clap::{crate_authors, crate_version, App, AppSettings, Arg, SubCommand};
+
+use pixo::ask::SchedulerKind;
+use pixo::load::data_file_paths;
+
+// Subcommand used when the first argument is not a subcommand, like `pixo <deck>`
+const DEFAULT_SUBCOMMAND: &str = "study";
+const SUBCOMMANDS: [&str; 12] = [
+    "study", "check", "stats", "list", "export", "print", "fmt", "add", "edit", "dedupe", "serve",
+    "help",
+];
+
+pub fn app() -> App<'static, 'static> {
+    App::new("Pixo")
+        .version(crate_version!())
+        .about("Pixo is a CLI fashcard app")
+        .author(crate_authors!())
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::VersionlessSubcommands)
+        .subcommand(study())
+        .subcommand(
+            SubCommand::with_name("check")
+                .about("Check that data files can be loaded.")
+                .arg(card_paths()),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("Show statistics about data files and their review history.")
+                .arg(card_paths().multiple(false)),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("List the cards of data files.")
+                .arg(card_paths()),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Export data files as a single deck.")
+                .arg(card_paths())
+                .arg(
+                    Arg::with_name("format")
+                        .help("Format of the exported deck.")
+                        .short("f")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["json", "tsv", "html"])
+                        .default_value("json"),
+                )
+                .arg(output()),
+        )
+        .subcommand(
+            SubCommand::with_name("print")
+                .about("Export a deck as printable double-sided cards (HTML).")
+                .arg(card_path().validator(is_json_file))
+                .arg(output()),
+        )
+        .subcommand(
+            SubCommand::with_name("fmt")
+                .about("Rewrite data files into their canonical form.")
+                .arg(card_paths())
+                .arg(
+                    Arg::with_name("check")
+                        .help("Only report the files needing formatting, without rewriting them.")
+                        .long("check"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("add")
+                .about("Add new cards to a data file, creating it if needed.")
+                .arg(card_path().validator(is_json_path)),
+        )
+        .subcommand(
+            SubCommand::with_name("edit")
+                .about("Edit a card of a data file.")
+                .arg(card_path().validator(is_json_file)),
+        )
+        .subcommand(
+            SubCommand::with_name("dedupe")
+                .about("Find the cards present several times across data files.")
+                .arg(card_paths())
+                .arg(
+                    Arg::with_name("merge")
+                        .help("Merge each duplicated card into its first occurrence, accepting the answers of all of them.")
+                        .long("merge"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("Serve the study sessions over a HTTP JSON API, on 127.0.0.1 only.")
+                .long_about("Serve the study sessions over a HTTP JSON API, on 127.0.0.1 only :\nPOST /deck {\"path\": \"deck.json\", \"profile\": \"exam\"} starts a session, the profile being optional\nGET /question returns the current question, or null once the session is over\nPOST /answer {\"answer\": \"...\"} and POST /giveup return the outcome of the question\nGET /stats returns the statistics of the deck")
+                .arg(
+                    Arg::with_name("port")
+                        .help("Port to listen on.")
+                        .long("port")
+                        .takes_value(true)
+                        .default_value("8080")
+                        .validator(is_port),
+                ),
+        )
+}
+
+fn study() -> App<'static, 'static> {
+    SubCommand::with_name("study")
+        .about("Study a deck. This is the default subcommand, so `pixo <deck>` works too.")
+        .arg(
+            card_path()
+                .validator(is_data_path)
+                .required_unless("resume"),
+        )
+        .arg(
+            Arg::with_name("machine")
+                .help("Speak JSON lines instead of text, for the programs wrapping pixo.")
+                .long_help("Speak JSON lines instead of text, for the programs wrapping pixo. Pixo writes events like {\"event\": \"question\", \"id\": 0, \"prompt\": \"Hello\", ...}, {\"event\": \"feedback\", \"outcome\": \"correct\", ...} and {\"event\": \"summary\", ...}, and reads messages like {\"type\": \"answer\", \"answer\": \"Coucou\"}, {\"type\": \"giveup\"} or {\"type\": \"quit\"}.")
+                .long("machine"),
+        )
+        .arg(
+            Arg::with_name("resume")
+                .help("Continue the last interrupted session where it stopped, with its own settings.")
+                .long("resume"),
+        )
+        .arg(
+            Arg::with_name("verso")
+                .help("Ask the verso insted of the rerco of each card.")
+                .short("v")
+                .long("verso")
+                .conflicts_with("random"),
+        )
+        .arg(
+            Arg::with_name("random")
+                .help("Inverse randomly the recto a verso of each card.")
+                .short("r")
+                .long("random"),
+        )
+        .arg(
+            Arg::with_name("random_ratio")
+                .help("Part of the cards inversed by the random mode, from 0 to 1. Turns on the random mode. [default: 0.25]")
+                .long("random-ratio")
+                .takes_value(true)
+                .value_name("RATIO")
+                .validator(is_ratio)
+                .conflicts_with("verso"),
+        )
+        .arg(
+            Arg::with_name("coin_flips")
+                .help("Inverse each card independently, with a probability of the random ratio, instead of exactly this part of the cards. Turns on the random mode.")
+                .long("coin-flips")
+                .conflicts_with("verso"),
+        )
+        .arg(
+            Arg::with_name("try")
+                .long("try")
+                .takes_value(true)
+                .help("Set the numbre of try for each question. 0 means infinity of try, until you give up with `:giveup`. [default: 1]")
+                .validator(is_number),
+        )
+        .arg(
+            Arg::with_name("all_cases")
+                .help("If random is turned to `true` and you have at lease two passes, then by using `all_cases` you assert that all cards will be present in recto and verso at lease one time, whatever the random ratio. Only the `passes` and `sequential` schedulers can do it.")
+                .alias("ac")
+                .long("all_cases")
+        )
+        .arg(
+            Arg::with_name("pass")
+                .help("Set the nombre of time the deck will be used. [default: 1]")
+                .short("p")
+                .long("pass")
+                .takes_value(true)
+                .validator(is_number_non_zero),
+        )
+        .arg(
+            Arg::with_name("limit")
+                .help("Ask at most this number of cards in the session, the due cards first.")
+                .long("limit")
+                .takes_value(true)
+                .value_name("N")
+                .validator(is_number_non_zero),
+        )
+        .arg(
+            Arg::with_name("new")
+                .help("Ask at most this number of cards never answered before.")
+                .long("new")
+                .takes_value(true)
+                .value_name("N")
+                .validator(is_number),
+        )
+        .arg(
+            Arg::with_name("mastery")
+                .help("Ask the cards until each one is answered correctly this number of times in a row, instead of a number of passes.")
+                .long("mastery")
+                .takes_value(true)
+                .value_name("STREAK")
+                .conflicts_with("pass")
+                .validator(is_number_non_zero),
+        )
+        .arg(
+            Arg::with_name("leitner")
+                .help("Use this number of Leitner boxes : only the due cards are asked, once, a correct answer moving a card to the next box and a wrong one back to the first box.")
+                .long("leitner")
+                .takes_value(true)
+                .value_name("BOXES")
+                .conflicts_with_all(&["pass", "mastery"])
+                .validator(is_number_non_zero),
+        )
+        .arg(
+            Arg::with_name("scheduler")
+                .help("Choose the order of the cards : `passes` asks the shuffled deck then the failed cards again, `sequential` the deck in the order of the file, `random` draws the cards at random, `weighted` draws the often and recently failed cards more often, and `spaced` only asks the cards due according to the history. [default: passes]")
+                .long("scheduler")
+                .takes_value(true)
+                .possible_values(&SchedulerKind::NAMES)
+                .conflicts_with("leitner"),
+        )
+        .arg(
+            Arg::with_name("time")
+                .help("Set the number of seconds to answer each question, after which the card is failed.")
+                .long("time")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .validator(is_number_non_zero),
+        )
+        .arg(
+            Arg::with_name("session_time")
+                .help("Set the number of minutes after which the session ends.")
+                .long("session-time")
+                .takes_value(true)
+                .value_name("MINUTES")
+                .validator(is_number_non_zero),
+        )
+        .arg(
+            Arg::with_name("ignore_case")
+                .help("Accept answers whatever their case.")
+                .long("ignore-case"),
+        )
+        .arg(
+            Arg::with_name("ignore_punctuation")
+                .help("Accept answers whatever their punctuation and spacing.")
+                .long("ignore-punctuation"),
+        )
+        .arg(
+            Arg::with_name("copy")
+                .help("After a failed card, type its answer once before going on.")
+                .long("copy"),
+        )
+        .arg(
+            Arg::with_name("cloze")
+                .help("Reveal one more letter of the answer after each wrong try, instead of the tips.")
+                .long("cloze"),
+        )
+        .arg(
+            Arg::with_name("tag")
+                .help("Only ask the cards with this tag. Can be used several times.")
+                .long("tag")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("exclude_tag")
+                .help("Never ask the cards with this tag. Can be used several times.")
+                .long("exclude-tag")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("profile")
+                .help("Use a profile of the configuration file.")
+                .long_help(
+                    "Use a profile of the configuration file ($XDG_CONFIG_HOME/pixo/config.json), like :\n{\n    \"profiles\": {\n        \"exam\": {\n            \"flip_mode\": \"random\",\n            \"all_cases\": true,\n            \"random_ratio\": 0.5,\n            \"coin_flips\": false,\n            \"tries\": 1,\n            \"passes\": 2,\n            \"limit\": 20,\n            \"new\": 5,\n            \"mastery\": null,\n            \"leitner\": null,\n            \"scheduler\": \"passes\",\n            \"time_limit\": 10,\n            \"session_time_limit\": 15,\n            \"ignore_case\": true,\n            \"ignore_punctuation\": false,\n            \"copy\": true,\n            \"cloze\": false,\n            \"tags\": [\"voc\"],\n            \"exclude_tags\": []\n        }\n    }\n}\nWARNING : These parametres can be overrided.",
+                )
+                .long("profile")
+                .takes_value(true)
+                .conflicts_with("default"),
+        )
+        .arg(
+            Arg::with_name("default")
+                .short("d")
+                .long("default")
+                .help("Use the default profil")
+                .long_help(
+                    "Use the default profil, same as `--profile default` :\nrandom = true\ntry = 2\nall_cases = true\npass = 2\nIt can be redefined in the configuration file.\nWARNING : These parametres can be overrided.",
+                ),
+        )
+}
+
+/// Insert the default subcommand when the first argument is not a
+/// subcommand, so `pixo <deck> [FLAGS]` keeps working.
+pub fn with_default_subcommand<I: IntoIterator<Item = OsString>>(args: I) -> Vec<OsString> {
+    let mut args = args.into_iter().collect::<Vec<_>>();
+
+    let insert = match args.get(1).and_then(|arg| arg.to_str()) {
+        Some("-h") | Some("--help") | Some("-V") | Some("--version") => false,
+        Some(arg) => !SUBCOMMANDS.contains(&arg),
+        None => false,
+    };
+    if insert {
+        args.insert(1, OsString::from(DEFAULT_SUBCOMMAND));
+    }
+
+    args
+}
+
+#[inline]
+fn card_path() -> Arg<'static, 'static> {
+    Arg::with_name("card_path").required(true).takes_value(true)
+}
+
+#[inline]
+fn card_paths() -> Arg<'static, 'static> {
+    card_path().multiple(true).validator(has_data_files)
+}
+
+#[inline]
+fn output() -> Arg<'static, 'static> {
+    Arg::with_name("output")
+        .help("Write into this file instead of the standard output.")
+        .short("o")
+        .long("output")
+        .takes_value(true)
+}
+
+#[inline]
+fn is_number(string: String) -> Result<(), String> {
+    string
+        .parse::<u32>()
+        .map(|_| ())
+        .map_err(|_| String::from("The value must be a natural number"))
+}
+
+#[inline]
+fn is_ratio(string: String) -> Result<(), String> {
+    match string.parse::<f64>() {
+        Ok(ratio) if (0. ..=1.).contains(&ratio) => Ok(()),
+        _ => Err(String::from("The value must be a number from 0 to 1")),
+    }
+}
+
+#[inline]
+fn is_port(string: String) -> Result<(), String> {
+    string
+        .parse::<u16>()
+        .map(|_| ())
+        .map_err(|_| String::from("The value must be a port number"))
+}
+
+#[inline]
+fn is_number_non_zero(string: String) -> Result<(), String> {
+    string
+        .parse::<u32>()
+        .map(|nbr| {
+            if nbr == 0 {
+                Err(String::from("Need to be at lease 1"))
+            } else {
+                Ok(())
+            }
+        })
+        .map_err(|_| String::from("The value must be a natural number"))
+        .and_then(convert::identity)
+}
+
+fn is_data_path(path: String) -> Result<(), String> {
+    let path = Path::new(&path);
+    if path.is_file() {
+        is_json_file(path.to_string_lossy().into_owned())
+    } else if path.is_dir() {
+        path.read_dir()
+            .map(|mut files| {
+                if files.any(|file| {
+                    file.unwrap_or_else(|_| panic!("Error during getting data in {:?}", path))
+                        .file_name()
+                        .into_string()
+                        .map(|string| {
+                            string
+                                .as_str()
+                                .split('.')
+                                .next_back()
+                                .map(|extention| extention == "json")
+                        })
+                        .ok()
+                        .flatten()
+                        .unwrap_or(false)
+                }) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "There don't are any json file in the folder {:?}",
+                        path
+                    ))
+                }
+            })
+            .unwrap_or_else(|_| Err(format!("Error during getting data in {:?}", path)))
+    } else {
+        Err(format!("The file {:?} does not exist.", path))
+    }
+}
+
+fn is_json_file(path: String) -> Result<(), String> {
+    let path = Path::new(&path);
+    if !path.is_file() {
+        Err(format!("The file {:?} does not exist.", path))
+    } else if path.extension() == Some("json".as_ref()) {
+        Ok(())
+    } else {
+        Err(format!("The file {:?} is not as json file.", path))
+    }
+}
+
+fn has_data_files(path: String) -> Result<(), String> {
+    let path = Path::new(&path);
+    if !path.exists() {
+        Err(format!("The file {:?} does not exist.", path))
+    } else if path.is_file() {
+        is_json_file(path.to_string_lossy().into_owned())
+    } else {
+        match data_file_paths(path) {
+            Ok(paths) if paths.is_empty() => Err(format!(
+                "There don't are any json file in the folder {:?}",
+                path
+            )),
+            Ok(_) => Ok(()),
+            Err(_) => Err(format!("Error during getting data in {:?}", path)),
+        }
+    }
+}
+
+fn is_json_path(path: String) -> Result<(), String> {
+    let path = Path::new(&path);
+    if path.is_dir() {
+        Err(format!("The path {:?} is a folder.", path))
+    } else if path.extension() == Some("json".as_ref()) {
+        Ok(())
+    } else {
+        Err(format!("The file {:?} is not as json file.", path))
+    }
+}
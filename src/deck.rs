@@ -1,19 +1,27 @@
+use std::convert::TryFrom;
+
 use rand::{
     seq::{index::sample, SliceRandom},
     Rng,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::ask::Ask;
-use crate::card::Card;
+use crate::ask::{Ask, RandomFlip};
+use crate::card::{Card, Sides};
 
+#[derive(Serialize, Deserialize)]
+#[serde(try_from = "DeckState")]
 pub struct Deck {
     cards: Vec<Card>,
+    // Order in which the cards are asked, so the index of a card is its id
+    order: Vec<usize>,
     question_index: usize,
 }
 
 impl Deck {
-    pub const fn new(cards: Vec<Card>) -> Self {
+    pub fn new(cards: Vec<Card>) -> Self {
         Self {
+            order: (0..cards.len()).collect(),
             cards,
             question_index: 0,
         }
@@ -28,9 +36,65 @@ impl Deck {
         self.cards.len()
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    // Keep only the cards whose id is in `ids`, the ids of the cards changing
+    pub fn keep(&mut self, ids: &[usize]) {
+        let mut id = 0;
+        self.cards.retain(|_| {
+            id += 1;
+            ids.contains(&(id - 1))
+        });
+        self.order = (0..self.cards.len()).collect();
+        self.question_index = 0;
+    }
+
     #[inline]
     pub fn suffle<R: Rng>(&mut self, rng: &mut R) {
-        self.cards.shuffle(rng);
+        self.order.shuffle(rng);
+    }
+
+    // Turn the only_verso cards, and add the reverse of the both_required cards
+    // so that both directions are asked in the session. Return the id before
+    // this of each card, the same for both directions.
+    pub fn orient(&mut self) -> Vec<usize> {
+        let mut origins = (0..self.cards.len()).collect::<Vec<_>>();
+        for index in 0..self.cards.len() {
+            let card = &mut self.cards[index];
+            match card.sides {
+                Sides::OnlyVerso => card.turn(),
+                Sides::BothRequired => {
+                    let mut reverse = card.clone();
+                    reverse.turn();
+                    self.cards.push(reverse);
+                    origins.push(index);
+                }
+                _ => (),
+            }
+        }
+        self.order = (0..self.cards.len()).collect();
+        self.question_index = 0;
+
+        origins
+    }
+
+    // Add the other direction of the cards which can be asked in any direction
+    // and for which `add` returns true, pushing the origin of each added card
+    pub fn add_turned<F: FnMut(&Card) -> bool>(&mut self, origins: &mut Vec<usize>, mut add: F) {
+        for index in 0..self.cards.len() {
+            let card = &self.cards[index];
+            if card.sides.is_any() && add(card) {
+                let mut turned = card.clone();
+                turned.turn();
+                self.cards.push(turned);
+                origins.push(origins[index]);
+            }
+        }
+        self.order = (0..self.cards.len()).collect();
+        self.question_index = 0;
     }
 
     #[inline]
@@ -38,10 +102,25 @@ impl Deck {
         self.cards.iter_mut().for_each(|card| card.flip())
     }
 
-    pub fn flip_random<R: Rng>(&mut self, rng: &mut R) {
-        let indexes = sample(rng, self.cards.len(), self.cards.len() >> 2);
-        for i in indexes {
-            self.flip_nth(i);
+    // Flip the ratio of the cards which can be asked in any direction, or each of
+    // them with a probability of the ratio if the flips are independent
+    pub fn flip_random<R: Rng>(&mut self, rng: &mut R, random_flip: RandomFlip) {
+        let ratio = random_flip.ratio();
+        let flippable = (0..self.cards.len())
+            .filter(|index| self.cards[*index].sides.is_any())
+            .collect::<Vec<_>>();
+
+        if random_flip.is_independent() {
+            for index in flippable {
+                if rng.gen_bool(ratio) {
+                    self.flip_nth(index);
+                }
+            }
+        } else {
+            let amount = (flippable.len() as f64 * ratio) as usize;
+            for i in sample(rng, flippable.len(), amount) {
+                self.flip_nth(flippable[i]);
+            }
         }
     }
 
@@ -57,6 +136,40 @@ impl Deck {
     pub fn get_card_by_index(&self, index: usize) -> Option<&Card> {
         self.cards.get(index)
     }
+
+    #[inline]
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+}
+
+// Deck as deserialized, before checking its order
+#[derive(Deserialize)]
+struct DeckState {
+    cards: Vec<Card>,
+    order: Vec<usize>,
+    question_index: usize,
+}
+
+impl TryFrom<DeckState> for Deck {
+    type Error = String;
+
+    fn try_from(state: DeckState) -> Result<Self, Self::Error> {
+        let mut ids = state.order.clone();
+        ids.sort_unstable();
+        if !ids.into_iter().eq(0..state.cards.len()) {
+            return Err("The order of the deck must contain each card once.".to_owned());
+        }
+        if state.question_index >= state.cards.len().max(1) {
+            return Err("The question index is out of the deck.".to_owned());
+        }
+
+        Ok(Self {
+            cards: state.cards,
+            order: state.order,
+            question_index: state.question_index,
+        })
+    }
 }
 
 impl Ask for Deck {
@@ -67,9 +180,56 @@ impl Ask for Deck {
 
     #[inline]
     fn get_card(&self) -> (&Card, usize) {
-        (
-            self.cards.get(self.question_index).unwrap(),
-            self.question_index,
-        )
+        let id = self.order[self.question_index];
+        (self.cards.get(id).unwrap(), id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Tip;
+    use crate::session::assert_round_trip;
+
+    fn deck() -> Deck {
+        let cards = ["one", "two", "three"]
+            .iter()
+            .map(|recto| {
+                Card::new(
+                    vec![recto.to_string()],
+                    vec!["x".to_owned()],
+                    Tip::None,
+                    Sides::Any,
+                )
+            })
+            .collect();
+        Deck::new(cards)
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut deck = deck();
+        deck.suffle(&mut rand::thread_rng());
+        deck.flip_nth(1);
+        deck.advance();
+
+        let loaded = assert_round_trip(&deck);
+        assert_eq!(loaded.question_index(), 1);
+        assert_eq!(loaded.get_card().1, deck.get_card().1);
+    }
+
+    #[test]
+    fn reject_invalid_order() {
+        let json = serde_json::to_value(deck()).unwrap();
+        for (field, value) in [
+            ("order", serde_json::json!([0, 0, 2])),
+            ("order", serde_json::json!([0, 1])),
+            ("order", serde_json::json!([0, 1, 3])),
+            ("question_index", serde_json::json!(3)),
+        ] {
+            let mut json = json.clone();
+            json[field] = value;
+            assert!(serde_json::from_value::<Deck>(json).is_err(), "{}", field);
+        }
     }
 }
@@ -0,0 +1,144 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+use std::path::PathBuf;
+
+use pixo::load::{load_data_file, save_data_file, CardJson, DataFile};
+
+// Words ignored at the start of a recto, so "To deliver" and "deliver" are the same card
+const IGNORED_PREFIXES: [&str; 4] = ["to", "a", "an", "the"];
+
+/// A card found in several places.
+pub struct Duplicate {
+    // (file index, card index)
+    pub occurrences: Vec<(usize, usize)>,
+}
+
+pub struct Dedupe {
+    files: Vec<(PathBuf, DataFile)>,
+    duplicates: Vec<Duplicate>,
+}
+
+impl Dedupe {
+    pub fn load(paths: Vec<PathBuf>) -> Result<Self, io::Error> {
+        let files = paths
+            .into_iter()
+            .map(|path| load_data_file(&path).map(|data_file| (path, data_file)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut groups: Vec<Vec<(usize, usize)>> = Vec::new();
+        let mut group_of: HashMap<String, usize> = HashMap::new();
+
+        for (file_index, (_, data_file)) in files.iter().enumerate() {
+            for (card_index, card) in data_file.questions().iter().enumerate() {
+                let keys = card.recto().iter().map(|recto| normalize(recto));
+                let group = keys
+                    .clone()
+                    .find_map(|key| group_of.get(&key).copied())
+                    .unwrap_or_else(|| {
+                        groups.push(Vec::new());
+                        groups.len() - 1
+                    });
+
+                groups[group].push((file_index, card_index));
+                for key in keys {
+                    group_of.entry(key).or_insert(group);
+                }
+            }
+        }
+
+        let duplicates = groups
+            .into_iter()
+            .filter(|occurrences| occurrences.len() > 1)
+            .map(|occurrences| Duplicate { occurrences })
+            .collect();
+
+        Ok(Self { files, duplicates })
+    }
+
+    #[inline]
+    pub fn duplicates(&self) -> &[Duplicate] {
+        &self.duplicates
+    }
+
+    #[inline]
+    pub fn card(&self, (file_index, card_index): (usize, usize)) -> &CardJson {
+        &self.files[file_index].1.questions()[card_index]
+    }
+
+    #[inline]
+    pub fn path(&self, file_index: usize) -> &PathBuf {
+        &self.files[file_index].0
+    }
+
+    // Return true if the occurrences of the card do not accept the same answers
+    pub fn is_conflict(&self, duplicate: &Duplicate) -> bool {
+        let mut versos = duplicate.occurrences.iter().map(|&occurrence| {
+            self.card(occurrence)
+                .verso()
+                .iter()
+                .map(|verso| normalize(verso))
+                .collect::<BTreeSet<_>>()
+        });
+
+        let first = versos.next().unwrap();
+        versos.any(|verso| verso != first)
+    }
+
+    /// Merge each duplicate into its first occurrence, whose answers become the
+    /// union of all answers, and save the modified files.
+    pub fn merge(mut self) -> Result<Vec<PathBuf>, io::Error> {
+        let mut removed: Vec<Vec<usize>> = vec![Vec::new(); self.files.len()];
+        // The cards keep the direction of their deck when moved
+        for (_, data_file) in &mut self.files {
+            data_file.inline_sides();
+        }
+
+        for duplicate in &self.duplicates {
+            let (first_file, first_card) = duplicate.occurrences[0];
+
+            for &(file_index, card_index) in &duplicate.occurrences[1..] {
+                let other = self.files[file_index].1.questions()[card_index].clone();
+                self.files[first_file].1.questions_mut()[first_card].merge(other);
+                removed[file_index].push(card_index);
+            }
+            self.files[first_file].1.questions_mut()[first_card].normalize();
+        }
+
+        let mut modified = Vec::new();
+        for (file_index, (path, data_file)) in self.files.iter_mut().enumerate() {
+            let is_first = self
+                .duplicates
+                .iter()
+                .any(|duplicate| duplicate.occurrences[0].0 == file_index);
+            if removed[file_index].is_empty() && !is_first {
+                continue;
+            }
+
+            removed[file_index].sort_unstable();
+            for &card_index in removed[file_index].iter().rev() {
+                data_file.questions_mut().remove(card_index);
+            }
+
+            save_data_file(path, data_file)?;
+            modified.push(path.clone());
+        }
+
+        Ok(modified)
+    }
+}
+
+/// Normalise a side of a card for comparison : lowercase, without punctuation
+/// nor extra spaces, and without a leading article.
+pub fn normalize(side: &str) -> String {
+    let lowercase = side.to_lowercase();
+    let mut words = lowercase
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+
+    if words.len() > 1 && IGNORED_PREFIXES.contains(&words[0]) {
+        words.remove(0);
+    }
+
+    words.join(" ")
+}
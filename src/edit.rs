@@ -0,0 +1,210 @@
+use std::io::{self, Stdin, Write};
+use std::path::Path;
+
+use pixo::card::Tip;
+use pixo::load::{load_data_file, save_data_file, CardJson, DataFile, Tag};
+
+// Typed by the user to remove an optional field while editing
+const CLEAR: &str = "-";
+
+/// Prompt new cards and append them to the data file, creating it if needed.
+pub fn add_cards(path: &Path) -> Result<(), io::Error> {
+    let stdin = io::stdin();
+
+    let mut data_file = if path.exists() {
+        load_data_file(path)?
+    } else {
+        DataFile::new()
+    };
+
+    loop {
+        let card = prompt_card(&stdin, None)?;
+        data_file.questions_mut().push(card);
+        save_data_file(path, &data_file)?;
+
+        println!("Card saved into {}.", path.display());
+        if !prompt_yes(&stdin, "Add another card ? [Y/n]")? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prompt the card to edit, then its new content, and write it back.
+pub fn edit_card(path: &Path) -> Result<(), io::Error> {
+    let stdin = io::stdin();
+
+    let mut data_file = load_data_file(path)?;
+
+    for (i, card) in data_file.questions().iter().enumerate() {
+        println!(
+            "{:>4}. {} -> {}",
+            i + 1,
+            card.recto().join(" OR "),
+            card.verso().join(" OR ")
+        );
+    }
+
+    let index = loop {
+        let answer = prompt(&stdin, "Card number to edit :")?;
+        match answer.parse::<usize>() {
+            Ok(nth) if nth >= 1 && nth <= data_file.questions().len() => break nth - 1,
+            _ => println!("The value must be a card number."),
+        }
+    };
+
+    println!(
+        "Press enter to keep the current value, or type `{}` to remove an optional one.",
+        CLEAR
+    );
+    let card = prompt_card(&stdin, Some(&data_file.questions()[index]))?;
+    data_file.questions_mut()[index] = card;
+    save_data_file(path, &data_file)?;
+
+    println!("Card saved into {}.", path.display());
+
+    Ok(())
+}
+
+fn prompt_card(stdin: &Stdin, current: Option<&CardJson>) -> Result<CardJson, io::Error> {
+    loop {
+        let recto = prompt_sides(stdin, "Recto", current.map(CardJson::recto))?;
+        let verso = prompt_sides(stdin, "Verso", current.map(CardJson::verso))?;
+        let tip = prompt_tip(stdin, current.map(CardJson::tip))?;
+        let tags = prompt_tags(stdin, current.map(CardJson::tags))?;
+
+        let mut card = CardJson::new(recto, verso, tip, tags);
+        card.normalize();
+
+        match card.validate() {
+            Ok(()) => return Ok(card),
+            Err(error) => println!("{}. Please try again.\n", error),
+        }
+    }
+}
+
+fn prompt_sides(
+    stdin: &Stdin,
+    name: &str,
+    current: Option<&[String]>,
+) -> Result<Vec<String>, io::Error> {
+    let description = format!("{} (one alternative per line, empty line to finish)", name);
+    let sides = prompt_list(stdin, &description, current)?;
+
+    Ok(match current {
+        Some(current) if sides.is_empty() => current.to_vec(),
+        _ => sides,
+    })
+}
+
+fn prompt_tip(stdin: &Stdin, current: Option<&Tip>) -> Result<Tip, io::Error> {
+    let recto = prompt_hints(stdin, "Recto hints", current.map(Tip::recto))?;
+    let verso = prompt_hints(
+        stdin,
+        "Verso hints (none for the same hints)",
+        current.map(Tip::verso),
+    )?;
+
+    Ok(match (recto.is_empty(), verso.is_empty()) {
+        (true, true) => Tip::None,
+        (false, true) => Tip::One(recto),
+        (true, false) => Tip::One(verso),
+        _ if recto == verso => Tip::One(recto),
+        _ => Tip::RectoVerso(recto, verso),
+    })
+}
+
+// Empty answer keeps the current hints, `CLEAR` removes them
+fn prompt_hints(
+    stdin: &Stdin,
+    name: &str,
+    current: Option<&[String]>,
+) -> Result<Vec<String>, io::Error> {
+    let current = current.filter(|hints| !hints.is_empty());
+    let description = format!("{} (one per line in order, empty line to finish)", name);
+    let hints = prompt_list(stdin, &description, current)?;
+
+    Ok(if hints.len() == 1 && hints[0] == CLEAR {
+        Vec::new()
+    } else if hints.is_empty() {
+        current.map(<[String]>::to_vec).unwrap_or_default()
+    } else {
+        hints
+    })
+}
+
+fn prompt_list(
+    stdin: &Stdin,
+    description: &str,
+    current: Option<&[String]>,
+) -> Result<Vec<String>, io::Error> {
+    match current {
+        Some(current) => println!("{} [{}] :", description, current.join(" OR ")),
+        None => println!("{} :", description),
+    }
+
+    let mut list = Vec::new();
+    loop {
+        let line = read_line(stdin)?;
+        if line.is_empty() {
+            break;
+        }
+        list.push(line);
+    }
+
+    Ok(list)
+}
+
+fn prompt_tags(stdin: &Stdin, current: Option<&[Tag]>) -> Result<Vec<Tag>, io::Error> {
+    let current = current
+        .filter(|tags| !tags.is_empty())
+        .map(|tags| tags.iter().map(Tag::as_str).collect::<Vec<_>>().join(" "));
+
+    let tags = prompt_optional(stdin, "Tags (separated by spaces)", current.as_deref())?;
+
+    Ok(tags
+        .map(|tags| tags.split_whitespace().map(|tag| Tag::from(tag.to_owned())).collect())
+        .unwrap_or_default())
+}
+
+// Empty answer keeps the current value, `CLEAR` removes it
+fn prompt_optional(
+    stdin: &Stdin,
+    name: &str,
+    current: Option<&str>,
+) -> Result<Option<String>, io::Error> {
+    let answer = match current {
+        Some(current) => prompt(stdin, &format!("{} [{}] :", name, current))?,
+        None => prompt(stdin, &format!("{} :", name))?,
+    };
+
+    Ok(match answer.as_str() {
+        CLEAR => None,
+        "" => current.map(str::to_owned),
+        _ => Some(answer),
+    })
+}
+
+fn prompt_yes(stdin: &Stdin, question: &str) -> Result<bool, io::Error> {
+    let answer = prompt(stdin, question)?;
+    Ok(!matches!(answer.to_lowercase().as_str(), "n" | "no"))
+}
+
+fn prompt(stdin: &Stdin, question: &str) -> Result<String, io::Error> {
+    print!("{} ", question);
+    io::stdout().flush()?;
+    read_line(stdin)
+}
+
+fn read_line(stdin: &Stdin) -> Result<String, io::Error> {
+    let mut line = String::new();
+    if stdin.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "The input was closed before the card was complete",
+        ));
+    }
+
+    Ok(line.trim().to_owned())
+}
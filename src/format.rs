@@ -0,0 +1,27 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use pixo::load::load_data_file;
+
+/// Rewrite a data file into its canonical form.
+///
+/// Return `true` if the file was not already formatted. In check mode, the
+/// file is left untouched.
+pub fn format_data_file(path: &Path, check: bool) -> Result<bool, io::Error> {
+    let original = fs::read_to_string(path)?;
+
+    let mut data_file = load_data_file(path)?;
+    data_file.normalize();
+    let formatted = data_file.to_canonical_string();
+
+    if formatted == original {
+        return Ok(false);
+    }
+
+    if !check {
+        fs::write(path, formatted)?;
+    }
+
+    Ok(true)
+}
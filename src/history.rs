@@ -0,0 +1,163 @@
+use std::collections::{btree_map, BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::Direction;
+use crate::{session, xdg};
+
+const HISTORY_FILE: &str = "history.json";
+
+/// Answers given to each direction of the cards across sessions, keyed by `CardKey`.
+#[derive(Serialize, Deserialize, Default)]
+pub struct History {
+    // Forward direction, the only one of the older histories
+    cards: HashMap<String, Vec<Review>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    reverse_cards: HashMap<String, Vec<Review>>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Review {
+    // Seconds since the UNIX epoch
+    pub time: u64,
+    pub correct: bool,
+    // Milliseconds taken to answer
+    #[serde(default)]
+    pub response_time: Option<u64>,
+}
+
+impl History {
+    /// Load the history saved in the data directory, or an empty one if there
+    /// is none yet.
+    pub fn load() -> Result<Self, io::Error> {
+        let path = match xdg::data_dir() {
+            Some(dir) => dir.join(HISTORY_FILE),
+            None => return Ok(Self::default()),
+        };
+
+        let mut history = if path.is_file() {
+            let buf_reader = BufReader::new(File::open(&path)?);
+            serde_json::from_reader(buf_reader)?
+        } else {
+            Self::default()
+        };
+        history.path = Some(path);
+
+        Ok(history)
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        if let Some(path) = &self.path {
+            session::write_json(path, self)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn record(&mut self, key: CardKey, correct: bool, response_time: Duration) {
+        let cards = match key.direction {
+            Direction::Forward => &mut self.cards,
+            Direction::Reverse => &mut self.reverse_cards,
+        };
+        cards.entry(key.recto).or_default().push(Review {
+            time: now(),
+            correct,
+            response_time: Some(response_time.as_millis() as u64),
+        })
+    }
+
+    #[inline]
+    pub fn reviews(&self, key: &CardKey) -> &[Review] {
+        let cards = match key.direction {
+            Direction::Forward => &self.cards,
+            Direction::Reverse => &self.reverse_cards,
+        };
+        cards.get(&key.recto).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// Identify a direction of a card in the history by its recto, as written in
+/// the data file.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CardKey {
+    pub recto: String,
+    pub direction: Direction,
+}
+
+impl CardKey {
+    #[inline]
+    pub fn new(recto: &[String], direction: Direction) -> Self {
+        Self {
+            recto: recto.join(" OR "),
+            direction,
+        }
+    }
+}
+
+// Values of the directions of the cards, keyed by `CardKey`
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub(crate) struct CardKeyMap<T> {
+    forward: BTreeMap<String, T>,
+    reverse: BTreeMap<String, T>,
+}
+
+impl<T> CardKeyMap<T> {
+    #[inline]
+    pub fn get(&self, key: &CardKey) -> Option<&T> {
+        match key.direction {
+            Direction::Forward => self.forward.get(&key.recto),
+            Direction::Reverse => self.reverse.get(&key.recto),
+        }
+    }
+
+    #[inline]
+    pub fn entry(&mut self, key: CardKey) -> btree_map::Entry<'_, String, T> {
+        match key.direction {
+            Direction::Forward => self.forward.entry(key.recto),
+            Direction::Reverse => self.reverse.entry(key.recto),
+        }
+    }
+}
+
+impl fmt::Display for CardKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.direction {
+            Direction::Forward => write!(f, "{}", self.recto),
+            Direction::Reverse => write!(f, "{} (reverse)", self.recto),
+        }
+    }
+}
+
+#[inline]
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::assert_round_trip;
+
+    #[test]
+    fn directions_apart() {
+        let reverse = CardKey::new(&["a".to_owned()], Direction::Reverse);
+        let forward = CardKey::new(&["a (reverse)".to_owned()], Direction::Forward);
+        let mut history = History::default();
+        history.record(reverse.clone(), true, Duration::ZERO);
+
+        let loaded = assert_round_trip(&history);
+        assert_eq!(loaded.reviews(&reverse).len(), 1);
+        assert!(loaded.reviews(&forward).is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::Direction;
+use crate::history::CardKey;
+use crate::{session, xdg};
+
+const LEITNER_FILE: &str = "leitner.json";
+
+/// Leitner boxes of each direction of the cards, keyed by `CardKey`.
+///
+/// A card in the box `n` (from 1) is asked every `2^(n-1)` sessions of its
+/// deck. A correct answer moves it to the next box, a wrong one back to the
+/// first box.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Leitner {
+    // Forward direction, the only one of the older files
+    #[serde(flatten)]
+    forward: Boxes,
+    #[serde(default)]
+    reverse: Boxes,
+    // Set again by `load` or `resume`
+    #[serde(skip, default = "one_box")]
+    box_count: NonZeroU32,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl Leitner {
+    /// Load the boxes saved in the data directory, or empty ones if there are
+    /// none yet.
+    pub fn load(box_count: NonZeroU32) -> Result<Self, io::Error> {
+        let path = Self::path();
+
+        let mut leitner = match &path {
+            Some(path) if path.is_file() => {
+                let buf_reader = BufReader::new(File::open(path)?);
+                serde_json::from_reader(buf_reader)?
+            }
+            _ => Self::new(box_count),
+        };
+        leitner.box_count = box_count;
+        leitner.path = path;
+
+        Ok(leitner)
+    }
+
+    /// Empty boxes, which are not saved.
+    #[inline]
+    pub fn new(box_count: NonZeroU32) -> Self {
+        Self {
+            forward: Boxes::default(),
+            reverse: Boxes::default(),
+            box_count,
+            path: None,
+        }
+    }
+
+    // Restore the settings not saved with the boxes, for a resumed session
+    #[inline]
+    pub fn resume(&mut self, box_count: NonZeroU32) {
+        self.box_count = box_count;
+        self.path = Self::path();
+    }
+
+    #[inline]
+    pub const fn box_count(&self) -> NonZeroU32 {
+        self.box_count
+    }
+
+    #[inline]
+    fn path() -> Option<PathBuf> {
+        xdg::data_dir().map(|dir| dir.join(LEITNER_FILE))
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        if let Some(path) = &self.path {
+            session::write_json(path, self)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn boxes(&self, direction: Direction) -> &Boxes {
+        match direction {
+            Direction::Forward => &self.forward,
+            Direction::Reverse => &self.reverse,
+        }
+    }
+
+    #[inline]
+    fn boxes_mut(&mut self, direction: Direction) -> &mut Boxes {
+        match direction {
+            Direction::Forward => &mut self.forward,
+            Direction::Reverse => &mut self.reverse,
+        }
+    }
+
+    // Box of a card, from 1, new cards being in the first one
+    #[inline]
+    pub fn box_of(&self, key: &CardKey) -> u32 {
+        self.boxes(key.direction)
+            .boxes
+            .get(&key.recto)
+            .copied()
+            .unwrap_or(1)
+            .clamp(1, self.box_count.get())
+    }
+
+    // Return true if the card has to be asked in this session
+    #[inline]
+    pub fn is_due(&self, key: &CardKey) -> bool {
+        self.boxes(key.direction)
+            .waits
+            .get(&key.recto)
+            .is_none_or(|wait| *wait == 0)
+    }
+
+    // A session of the deck of the card went by without asking it
+    #[inline]
+    pub fn skip(&mut self, key: &CardKey) {
+        if let Some(wait) = self.boxes_mut(key.direction).waits.get_mut(&key.recto) {
+            *wait = wait.saturating_sub(1);
+        }
+    }
+
+    #[inline]
+    pub fn promote(&mut self, key: CardKey) {
+        let next = (self.box_of(&key) + 1).min(self.box_count.get());
+        let boxes = self.boxes_mut(key.direction);
+        boxes
+            .waits
+            .insert(key.recto.clone(), (1 << (next - 1).min(31)) - 1);
+        boxes.boxes.insert(key.recto, next);
+    }
+
+    #[inline]
+    pub fn demote(&mut self, key: CardKey) {
+        let boxes = self.boxes_mut(key.direction);
+        boxes.waits.remove(&key.recto);
+        boxes.boxes.insert(key.recto, 1);
+    }
+}
+
+// Boxes of one direction of the cards, keyed by their recto
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct Boxes {
+    #[serde(default)]
+    boxes: HashMap<String, u32>,
+    // Sessions of its deck a card still has to skip before being asked, none if missing
+    #[serde(default)]
+    waits: HashMap<String, u32>,
+}
+
+#[inline]
+const fn one_box() -> NonZeroU32 {
+    NonZeroU32::MIN
+}
@@ -0,0 +1,24 @@
+//! Pixo asks the cards of a deck until they are learned.
+//!
+//! Load a data file with `load_data_file`, turn it into a `Deck`, then set up
+//! the session with `AskerBuilder` and run it in the terminal, or drive it
+//! yourself with the `Engine` of `AskerBuilder::build_engine`.
+
+pub mod ask;
+pub mod card;
+pub mod deck;
+pub mod history;
+pub mod leitner;
+pub mod load;
+mod session;
+pub mod stats;
+pub mod xdg;
+
+pub use ask::{
+    Asker, AskerBuilder, Engine, FlipMode, HintMode, Matching, Outcome, Question, RandomFlip,
+    Scheduler, SchedulerKind, Tries,
+};
+pub use card::{Card, Direction, Sides, Tip};
+pub use deck::Deck;
+pub use history::History;
+pub use load::{data_file_paths, load_data_file, save_data_file, CardJson, DataFile, Tag};
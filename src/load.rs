@@ -1,52 +1,271 @@
-use std::{io::BufReader, path::Path};
+use std::{
+    io::BufReader,
+    path::{Path, PathBuf},
+};
 
-use std::fs::File;
+use std::fs::{self, File};
 use std::io;
 
-use crate::card::{Card, Tip};
+use crate::card::{validate_sides, Card, Sides, Tip};
 use crate::deck::Deck;
 
-use serde::{de::Visitor, Deserialize, Deserializer};
+use serde::{
+    de::Visitor,
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::ser::PrettyFormatter;
 
 pub fn load_data_file(path: &Path) -> Result<DataFile, io::Error> {
     let file = File::open(path)?;
     let buf_reader = BufReader::new(file);
 
     let data_file: DataFile = serde_json::from_reader(buf_reader)?;
+    data_file
+        .validate()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
 
     Ok(data_file)
 }
 
-#[derive(Deserialize)]
+pub fn save_data_file(path: &Path, data_file: &DataFile) -> Result<(), io::Error> {
+    fs::write(path, data_file.to_canonical_string())
+}
+
+// List the data files (.json files) of a folder and its sub-folders, or the file itself
+pub fn data_file_paths(path: &Path) -> Result<Vec<PathBuf>, io::Error> {
+    if path.is_file() {
+        return Ok(vec![path.to_owned()]);
+    }
+
+    let mut paths = Vec::new();
+    for entry in path.read_dir()? {
+        let entry_path = entry?.path();
+        if entry_path.is_dir() {
+            paths.append(&mut data_file_paths(&entry_path)?);
+        } else if entry_path.extension() == Some("json".as_ref()) {
+            paths.push(entry_path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+#[derive(Deserialize, Serialize, Clone)]
 pub struct CardJson {
     #[serde(alias = "qst")]
     #[serde(deserialize_with = "single_or_list")]
+    #[serde(serialize_with = "to_single_or_list")]
     recto: Vec<String>,
     #[serde(alias = "answer")]
     #[serde(deserialize_with = "single_or_list")]
+    #[serde(serialize_with = "to_single_or_list")]
     verso: Vec<String>,
     #[serde(alias = "tips")]
     #[serde(deserialize_with = "tip_to_Tip")]
+    #[serde(serialize_with = "Tip_to_tip")]
     #[serde(default = "tip_none")]
+    #[serde(skip_serializing_if = "Tip::is_none")]
     tip: Tip,
     #[serde(default = "vec_empty")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     tags: Vec<Tag>,
 }
 
-#[derive(Deserialize)]
+impl CardJson {
+    #[inline]
+    pub const fn new(recto: Vec<String>, verso: Vec<String>, tip: Tip, tags: Vec<Tag>) -> Self {
+        Self {
+            recto,
+            verso,
+            tip,
+            tags,
+        }
+    }
+
+    #[inline]
+    pub fn recto(&self) -> &[String] {
+        &self.recto
+    }
+
+    #[inline]
+    pub fn verso(&self) -> &[String] {
+        &self.verso
+    }
+
+    #[inline]
+    pub const fn tip(&self) -> &Tip {
+        &self.tip
+    }
+
+    #[inline]
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    // Directions set by the tags of the card, if any
+    #[inline]
+    pub fn sides(&self) -> Option<Sides> {
+        self.tags.iter().find_map(Tag::sides)
+    }
+
+    // Add the rectos, versos and tags of `other` which are missing, and its tip if there is none.
+    // The card keeps its own direction tag if both have one.
+    pub fn merge(&mut self, other: Self) {
+        for recto in other.recto {
+            if !self.recto.contains(&recto) {
+                self.recto.push(recto)
+            }
+        }
+        for verso in other.verso {
+            if !self.verso.contains(&verso) {
+                self.verso.push(verso)
+            }
+        }
+        for tag in other.tags {
+            if !self.tags.contains(&tag) && (tag.sides().is_none() || self.sides().is_none()) {
+                self.tags.push(tag)
+            }
+        }
+        if self.tip.is_none() {
+            self.tip = other.tip
+        }
+    }
+
+    // Check that the card can be asked in both ways
+    pub fn validate(&self) -> Result<(), String> {
+        validate_sides(&self.recto, &self.verso)?;
+        if self.tags.iter().filter_map(Tag::sides).count() > 1 {
+            Err(String::from("There are several direction tags"))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Put the card in its canonical form without changing its meaning
+    pub fn normalize(&mut self) {
+        self.recto.iter_mut().for_each(trim_in_place);
+        self.verso.iter_mut().for_each(trim_in_place);
+        match &mut self.tip {
+            Tip::None => (),
+            Tip::One(hints) => hints.iter_mut().for_each(trim_in_place),
+            Tip::RectoVerso(recto, verso) => {
+                recto.iter_mut().for_each(trim_in_place);
+                verso.iter_mut().for_each(trim_in_place);
+            }
+        }
+
+        self.tags.iter_mut().for_each(|tag| {
+            if let Tag::Unknow(tag) = tag {
+                trim_in_place(tag)
+            }
+        });
+        self.tags.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        self.tags.dedup();
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Clone)]
 pub struct DataFile {
+    // Directions of the cards without direction tag
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Sides::is_any")]
+    sides: Sides,
     questions: Vec<CardJson>,
 }
 
-impl From<CardJson> for Card {
-    fn from(card_json: CardJson) -> Self {
-        Self::new(
-            card_json.recto,
-            card_json.verso,
-            card_json.tip,
-            card_json.tags.contains(&Tag::OnlyRecto),
+impl DataFile {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            sides: Sides::Any,
+            questions: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub const fn sides(&self) -> Sides {
+        self.sides
+    }
+
+    #[inline]
+    pub fn sides_of(&self, card: &CardJson) -> Sides {
+        card.sides().unwrap_or(self.sides)
+    }
+
+    // Card asked for a card of the data file, with the directions of the deck
+    // if it has no direction tag
+    #[inline]
+    pub fn card(&self, card: &CardJson) -> Card {
+        Card::new(
+            card.recto.clone(),
+            card.verso.clone(),
+            card.tip.clone(),
+            self.sides_of(card),
         )
     }
+
+    // Tag the cards without direction tag with the directions of the deck, so
+    // they can be moved to another deck
+    pub fn inline_sides(&mut self) {
+        if let Some(tag) = Tag::from_sides(self.sides) {
+            for card in &mut self.questions {
+                if card.sides().is_none() {
+                    card.tags.push(tag.clone());
+                }
+            }
+        }
+        self.sides = Sides::Any;
+    }
+
+    #[inline]
+    pub fn questions(&self) -> &[CardJson] {
+        &self.questions
+    }
+
+    #[inline]
+    pub fn questions_mut(&mut self) -> &mut Vec<CardJson> {
+        &mut self.questions
+    }
+
+    // Keep the cards with one of `tags` (all if empty) and none of `exclude_tags`
+    pub fn filter_tags(&mut self, tags: &[String], exclude_tags: &[String]) {
+        self.questions.retain(|card| {
+            (tags.is_empty() || card.tags.iter().any(|tag| tags.iter().any(|t| t == tag.as_str())))
+                && !card
+                    .tags
+                    .iter()
+                    .any(|tag| exclude_tags.iter().any(|t| t == tag.as_str()))
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.questions
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, card)| {
+                card.validate()
+                    .map_err(|error| format!("Card {} : {}.", i + 1, error))
+            })
+    }
+
+    #[inline]
+    pub fn normalize(&mut self) {
+        self.questions.iter_mut().for_each(CardJson::normalize)
+    }
+
+    // Serialize the data file with keys `recto`, `verso`, `tip` and `tags`, indented by 4 spaces
+    pub fn to_canonical_string(&self) -> String {
+        let mut buffer = Vec::new();
+        let formatter = PrettyFormatter::with_indent(b"    ");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+        self.serialize(&mut serializer).unwrap();
+
+        let mut string = String::from_utf8(buffer).unwrap();
+        string.push('\n');
+        string
+    }
 }
 
 impl From<DataFile> for Deck {
@@ -54,24 +273,70 @@ impl From<DataFile> for Deck {
         Self::new(
             data_file
                 .questions
-                .into_iter()
-                .map(Card::from)
-                .collect::<Vec<Card>>(),
+                .iter()
+                .map(|card| data_file.card(card))
+                .collect(),
         )
     }
 }
 
-#[derive(Deserialize, PartialEq)]
+#[derive(Deserialize, Serialize, PartialEq, Clone)]
 #[serde(from = "String")]
+#[serde(into = "String")]
 pub enum Tag {
     OnlyRecto,
+    OnlyVerso,
+    BothRequired,
     Unknow(String),
 }
 
+impl Tag {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::OnlyRecto => "only_recto",
+            Self::OnlyVerso => "only_verso",
+            Self::BothRequired => "both_required",
+            Self::Unknow(string) => string,
+        }
+    }
+
+    // Directions set by the tag, if it is a direction tag
+    #[inline]
+    pub const fn sides(&self) -> Option<Sides> {
+        match self {
+            Self::OnlyRecto => Some(Sides::OnlyRecto),
+            Self::OnlyVerso => Some(Sides::OnlyVerso),
+            Self::BothRequired => Some(Sides::BothRequired),
+            Self::Unknow(_) => None,
+        }
+    }
+
+    #[inline]
+    pub const fn from_sides(sides: Sides) -> Option<Self> {
+        match sides {
+            Sides::Any => None,
+            Sides::OnlyRecto => Some(Self::OnlyRecto),
+            Sides::OnlyVerso => Some(Self::OnlyVerso),
+            Sides::BothRequired => Some(Self::BothRequired),
+        }
+    }
+}
+
+impl From<Tag> for String {
+    fn from(tag: Tag) -> Self {
+        match tag {
+            Tag::Unknow(string) => string,
+            tag => Self::from(tag.as_str()),
+        }
+    }
+}
+
 impl From<String> for Tag {
     fn from(string: String) -> Self {
         match string.as_str() {
             "only_recto" => Self::OnlyRecto,
+            "only_verso" => Self::OnlyVerso,
+            "both_required" => Self::BothRequired,
             _ => Self::Unknow(string),
         }
     }
@@ -111,21 +376,100 @@ fn single_or_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Stri
     deserializer.deserialize_any(visitor)
 }
 
+#[derive(Deserialize)]
+struct SingleOrList(#[serde(deserialize_with = "single_or_list")] Vec<String>);
+
+// A tip is either a string (one hint), a list of two strings (recto and verso hints)
+// or an object of hint lists : `{"both": [...]}` or `{"recto": [...], "verso": [...]}`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TipJson {
+    List(SingleOrList),
+    Hints(TipHints),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TipHints {
+    both: Option<SingleOrList>,
+    recto: Option<SingleOrList>,
+    verso: Option<SingleOrList>,
+}
+
 #[allow(non_snake_case)]
 fn tip_to_Tip<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Tip, D::Error> {
-    let mut tips = single_or_list(deserializer)?;
-
-    Ok(match tips.len() {
-        0 => Tip::None,
-        1 => Tip::One(tips.pop().unwrap()),
-        _ => {
-            let verso = tips.pop().unwrap();
-            let recto = tips.pop().unwrap();
-            Tip::RectoVerso(recto, verso)
+    Ok(match TipJson::deserialize(deserializer)? {
+        TipJson::List(SingleOrList(mut tips)) => match tips.len() {
+            0 => Tip::None,
+            1 => Tip::One(tips),
+            _ => {
+                let verso = tips.pop().unwrap();
+                let recto = tips.pop().unwrap();
+                Tip::RectoVerso(vec![recto], vec![verso])
+            }
+        },
+        TipJson::Hints(TipHints { both, recto, verso }) => {
+            let both = both.map(|SingleOrList(hints)| hints).unwrap_or_default();
+            match (recto, verso) {
+                (None, None) if both.is_empty() => Tip::None,
+                (None, None) => Tip::One(both),
+                (recto, verso) => Tip::RectoVerso(
+                    recto.map_or_else(|| both.clone(), |SingleOrList(hints)| hints),
+                    verso.map_or(both, |SingleOrList(hints)| hints),
+                ),
+            }
         }
     })
 }
 
+fn to_single_or_list<S: Serializer>(list: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+    if let [single] = list {
+        serializer.serialize_str(single)
+    } else {
+        let mut seq = serializer.serialize_seq(Some(list.len()))?;
+        for string in list {
+            seq.serialize_element(string)?;
+        }
+        seq.end()
+    }
+}
+
+#[allow(non_snake_case)]
+fn Tip_to_tip<S: Serializer>(tip: &Tip, serializer: S) -> Result<S::Ok, S::Error> {
+    match tip {
+        Tip::None => serializer.serialize_none(),
+        Tip::One(hints) if hints.len() == 1 => serializer.serialize_str(&hints[0]),
+        Tip::One(hints) => {
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry("both", hints)?;
+            map.end()
+        }
+        Tip::RectoVerso(recto, verso) if recto.len() == 1 && verso.len() == 1 => {
+            let mut seq = serializer.serialize_seq(Some(2))?;
+            seq.serialize_element(&recto[0])?;
+            seq.serialize_element(&verso[0])?;
+            seq.end()
+        }
+        Tip::RectoVerso(recto, verso) => {
+            let mut map = serializer.serialize_map(None)?;
+            if !recto.is_empty() {
+                map.serialize_entry("recto", recto)?;
+            }
+            if !verso.is_empty() {
+                map.serialize_entry("verso", verso)?;
+            }
+            map.end()
+        }
+    }
+}
+
+#[inline]
+fn trim_in_place(string: &mut String) {
+    if string.trim().len() != string.len() {
+        *string = string.trim().to_owned();
+    }
+}
+
 const fn tip_none() -> Tip {
     Tip::None
 }
@@ -1,178 +1,388 @@
-mod ask;
-mod card;
-mod deck;
-mod load;
+mod cli;
+mod dedupe;
+mod edit;
+mod format;
+mod print;
+mod profile;
+mod serve;
 
+use std::env;
 use std::error::Error;
-use std::num::NonZeroU32;
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process::exit;
-use std::{convert, path::Path};
+use std::str::FromStr;
 
-use clap::{crate_authors, crate_version, App, Arg};
+use clap::ArgMatches;
+use rand::rngs::ThreadRng;
 
-use crate::ask::{AskerBuilder, FlipMode};
-use crate::deck::Deck;
-use crate::load::load_data_file;
+use pixo::ask::{Asker, AskerBuilder, Event};
+use pixo::deck::Deck;
+use pixo::history::History;
+use pixo::load::{data_file_paths, load_data_file, DataFile};
+use pixo::stats::Stats;
+
+use crate::dedupe::Dedupe;
+use crate::profile::{Config, Profile, ProfileFlipMode, DEFAULT_PROFILE};
 
 fn main() -> Result<(), Box<dyn Error>> {
     // Create Clap app
-    let matches = App::new("Pixo")
-        .version(crate_version!())
-        .about("Pixo is a CLI fashcard app")
-        .author(crate_authors!())
-        .arg(
-            Arg::with_name("card_path")
-                .required(true)
-                .takes_value(true)
-                .validator(|path| {
-                    let path = Path::new(&path);
-                    if path.is_file() {
-                        if path.extension().unwrap() == "json" {
-                            Ok(())
-                        } else {
-                            Err(format!("The file {:?} is not as json file.", path))
-                        }
-                    } else if path.is_dir() {
-                        path.read_dir()
-                            .map(|mut files| {
-                                if files.any(|file| {
-                                    file.expect(&format!("Error during getting data in {:?}", path))
-                                        .file_name()
-                                        .into_string()
-                                        .map(|string| {
-                                            string
-                                                .as_str()
-                                                .split('.')
-                                                .last()
-                                                .map(|extention| extention == "json")
-                                        })
-                                        .ok()
-                                        .flatten()
-                                        .unwrap_or(false)
-                                }) {
-                                    Ok(())
-                                } else {
-                                    Err(format!(
-                                        "There don't are any json file in the folder {:?}",
-                                        path
-                                    ))
-                                }
-                            })
-                            .unwrap_or_else(|_ |Err(format!("Error during getting data in {:?}", path)))
-                    } else {
-                        Err(format!("The file {:?} does not exist.", path))
-                    }
-                }),
-        )
-        .arg(
-            Arg::with_name("verso")
-                .help("Ask the verso insted of the rerco of each card.")
-                .short("v")
-                .long("verso")
-                .conflicts_with("random"),
-        )
-        .arg(
-            Arg::with_name("random")
-                .help("Inverse randomly the recto a verso of each card.")
-                .short("r")
-                .long("random"),
-        )
-        .arg(
-            Arg::with_name("try")
-                .long("try")
-                .takes_value(true)
-                .help("Set the numbre of try for each question. 0 means infinity of try.")
-                .default_value("1")
-                .default_value_if("default", None, "2")
-                .validator(is_number_non_zero),
-        )
-        .arg(
-            Arg::with_name("all_cases")
-                .help("If random is turned to `true` and you have at lease two passes, then by using `all_cases` you assert that all cards will be present in recto and verso at lease one time.")
-                .requires("random")
-                .alias("ac")
-                .long("all_cases")
-        )
-        .arg(
-            Arg::with_name("pass")
-                .help("Set the nombre of time the deck will be used.")
-                .short("p")
-                .long("pass")
-                .validator(is_number_non_zero)
-                .default_value("1")
-                .default_value_if("default", None, "2"),
-        )
-        .arg(
-            Arg::with_name("default")
-                .short("d")
-                .long("default")
-                .help("Use the default profil")
-                .long_help(
-                    "Use the default profil :\nrandom = true\ntry = 2\nall_cases = true\npass = 2\nWARNING : These parametres can be overrided.",
-                ),
+    let matches = cli::app().get_matches_from(cli::with_default_subcommand(env::args_os()));
+
+    match matches.subcommand() {
+        ("study", Some(matches)) => study(matches),
+        ("check", Some(matches)) => check(matches),
+        ("stats", Some(matches)) => stats(matches),
+        ("list", Some(matches)) => list(matches),
+        ("export", Some(matches)) => export(matches),
+        ("print", Some(matches)) => print(matches),
+        ("fmt", Some(matches)) => fmt(matches),
+        ("add", Some(matches)) => {
+            edit::add_cards(Path::new(matches.value_of("card_path").unwrap()))?;
+            Ok(())
+        }
+        ("edit", Some(matches)) => {
+            edit::edit_card(Path::new(matches.value_of("card_path").unwrap()))?;
+            Ok(())
+        }
+        ("dedupe", Some(matches)) => dedupe(matches),
+        ("serve", Some(matches)) => serve::serve(matches.value_of("port").unwrap().parse()?),
+        _ => unreachable!(),
+    }
+}
+
+fn study(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    // Continue the last interrupted session, with its own settings
+    if matches.is_present("resume") {
+        let asker = Asker::<ThreadRng>::resume()?.ok_or("There is no session to resume.")?;
+        return run(asker, matches);
+    }
+
+    // Get the profile, whose settings are overrided by the command line
+    let mut profile = if matches.is_present("default") {
+        Config::load()?.profile(DEFAULT_PROFILE)
+    } else if let Some(name) = matches.value_of("profile") {
+        Some(
+            Config::load()?
+                .profile(name)
+                .ok_or_else(|| format!("The profile {:?} does not exist.", name))?,
         )
-        .get_matches();
+    } else {
+        None
+    }
+    .unwrap_or_default();
+    override_profile(&mut profile, matches);
 
     // Get data from path given by the user
     let input = Path::new(matches.value_of("card_path").unwrap());
     if input.is_dir() {
-        println!("Pixo can not read a folder of data files (.json files) yet.");
+        let message = "Pixo can not read a folder of data files (.json files) yet.";
+        if matches.is_present("machine") {
+            Event::Error {
+                message: message.to_owned(),
+            }
+            .emit()?;
+        } else {
+            println!("{}", message);
+        }
+        exit(0)
+    }
+    let mut data_file = load_data_file(input)?;
+
+    // Keep the cards matching the TAG filters
+    data_file.filter_tags(&profile.tags, &profile.exclude_tags);
+    // In MACHINE mode the session ends at once, with an empty summary
+    if data_file.questions().is_empty() && !matches.is_present("machine") {
+        println!("There is no card to ask.");
         exit(0)
     }
-    let data_file = load_data_file(input)?;
 
     // Create deck and asker builder
     let deck = Deck::from(data_file);
     let mut asker = AskerBuilder::new(deck, rand::thread_rng());
+    asker.history(History::load()?);
+    asker.checkpoint(true);
+    profile.configure(&mut asker)?;
 
-    // Check DEFAULT profile
-    if matches.is_present("default") {
-        if !matches.is_present("verso") {
-            asker.flip_mode(FlipMode::Random(true))
-        }
-        asker.tries(NonZeroU32::new(2).unwrap());
-        asker.max_cycle(NonZeroU32::new(2).unwrap())
+    // Build and run pixo !
+    run(asker.build(), matches)
+}
+
+// Run the session in the terminal, or with JSON lines in MACHINE mode
+fn run(asker: Asker<ThreadRng>, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    if matches.is_present("machine") {
+        asker.run_machine()?;
+    } else {
+        asker.run()?;
     }
 
+    Ok(())
+}
+
+// Override the settings of the profile with the command line
+fn override_profile(profile: &mut Profile, matches: &ArgMatches) {
     // Check VERSO, RANDOM and ALL_CASE mode
     if matches.is_present("verso") {
-        asker.flip_mode(FlipMode::Verso)
+        profile.flip_mode = Some(ProfileFlipMode::Verso);
     } else if matches.is_present("random") {
-        asker.flip_mode(FlipMode::Random(matches.is_present("all_cases")));
+        profile.flip_mode = Some(ProfileFlipMode::Random);
+    }
+    if matches.is_present("all_cases") {
+        profile.all_cases = Some(true);
     }
 
-    // Get number of CYCLE
-    if let Some(pass) = matches.value_of("pass") {
-        let max_cycle = NonZeroU32::new(pass.parse::<u32>().unwrap()).unwrap();
+    // Get the RANDOM RATIO and the COIN FLIPS, which imply the random mode
+    if let Some(ratio) = parse_value(matches, "random_ratio") {
+        profile.flip_mode = Some(ProfileFlipMode::Random);
+        profile.random_ratio = Some(ratio);
+    }
+    if matches.is_present("coin_flips") {
+        profile.flip_mode = Some(ProfileFlipMode::Random);
+        profile.coin_flips = Some(true);
+    }
+
+    // Get number of CYCLE, replacing the mastery and the Leitner boxes of the profile
+    if let Some(pass) = parse_value(matches, "pass") {
+        profile.passes = Some(pass);
+        profile.mastery = None;
+        profile.leitner = None;
+    }
 
-        asker.max_cycle(max_cycle)
+    // Get the LIMIT of cards and of NEW cards
+    if let Some(limit) = parse_value(matches, "limit") {
+        profile.limit = Some(limit);
+    }
+    if let Some(new_cards) = parse_value(matches, "new") {
+        profile.new = Some(new_cards);
+    }
+
+    // Get the MASTERY streak, the number of LEITNER boxes and the SCHEDULER
+    if let Some(mastery) = parse_value(matches, "mastery") {
+        profile.mastery = Some(mastery);
+        profile.leitner = None;
+    }
+    if let Some(scheduler) = parse_value(matches, "scheduler") {
+        profile.scheduler = Some(scheduler);
+        profile.leitner = None;
+    }
+    if let Some(leitner) = parse_value(matches, "leitner") {
+        profile.leitner = Some(leitner);
+    }
+    // Drop the ALL_CASES of the profile if the scheduler chosen can not honour it
+    if (matches.is_present("scheduler") || matches.is_present("leitner"))
+        && !matches.is_present("all_cases")
+        && !profile.supports_all_cases()
+    {
+        profile.all_cases = None;
     }
 
     // Get number of TRY
-    if let Some(tries) = matches.value_of("try") {
-        let tries = NonZeroU32::new(tries.parse::<u32>().unwrap()).unwrap();
+    if let Some(tries) = parse_value(matches, "try") {
+        profile.tries = Some(tries);
+    }
 
-        asker.tries(tries)
+    // Get the TIME limits
+    if let Some(time) = parse_value(matches, "time") {
+        profile.time_limit = Some(time);
+    }
+    if let Some(session_time) = parse_value(matches, "session_time") {
+        profile.session_time_limit = Some(session_time);
     }
 
-    // Build and run pixo !
-    let asker = asker.build();
-    asker.run()?;
+    // Get the MATCHING options, and check COPY and CLOZE mode
+    for (name, setting) in [
+        ("ignore_case", &mut profile.ignore_case),
+        ("ignore_punctuation", &mut profile.ignore_punctuation),
+        ("copy", &mut profile.copy),
+        ("cloze", &mut profile.cloze),
+    ] {
+        if matches.is_present(name) {
+            *setting = Some(true);
+        }
+    }
 
-    Ok(())
+    // Get the TAG filters
+    if let Some(values) = matches.values_of("tag") {
+        profile.tags = values.map(str::to_owned).collect();
+    }
+    if let Some(values) = matches.values_of("exclude_tag") {
+        profile.exclude_tags = values.map(str::to_owned).collect();
+    }
 }
 
+// Parse the value of an argument, already checked by its validator
 #[inline]
-fn is_number_non_zero(string: String) -> Result<(), String> {
-    string
-        .parse::<u32>()
-        .map(|nbr| {
-            if nbr == 0 {
-                Err(String::from("Need to be at lease 1"))
+fn parse_value<T: FromStr>(matches: &ArgMatches, name: &str) -> Option<T>
+where
+    T::Err: Debug,
+{
+    matches.value_of(name).map(|value| value.parse().unwrap())
+}
+
+fn check(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut invalid = 0;
+
+    for path in card_paths(matches)? {
+        if let Err(error) = load_data_file(&path) {
+            invalid += 1;
+            println!("{} : {}", path.display(), error);
+        }
+    }
+
+    if invalid != 0 {
+        exit(1)
+    }
+
+    Ok(())
+}
+
+fn stats(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let data_files = load_data_files(matches)?;
+
+    print!("{}", Stats::new(&data_files, &History::load()?));
+
+    Ok(())
+}
+
+fn list(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    for path in card_paths(matches)? {
+        println!("{}", path.display());
+        for (i, card) in load_data_file(&path)?.questions().iter().enumerate() {
+            println!(
+                "{:>4}. {} -> {}",
+                i + 1,
+                card.recto().join(" OR "),
+                card.verso().join(" OR ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn export(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut data_file = DataFile::new();
+    for mut other in load_data_files(matches)? {
+        other.inline_sides();
+        data_file.questions_mut().append(other.questions_mut());
+    }
+
+    let exported = match matches.value_of("format").unwrap() {
+        "tsv" => {
+            let mut tsv = String::new();
+            for card in data_file.questions() {
+                tsv.push_str(&format!(
+                    "{}\t{}\t{}\n",
+                    card.recto().join(" OR "),
+                    card.verso().join(" OR "),
+                    card.tip().recto().join(" / ")
+                ));
+            }
+            tsv
+        }
+        "html" => print::render_html(&Deck::from(data_file), "Pixo"),
+        _ => {
+            data_file.normalize();
+            data_file.to_canonical_string()
+        }
+    };
+
+    write_output(matches, &exported)?;
+
+    Ok(())
+}
+
+fn print(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let input = Path::new(matches.value_of("card_path").unwrap());
+    let deck = Deck::from(load_data_file(input)?);
+    let title = input.file_stem().unwrap().to_string_lossy();
+
+    write_output(matches, &print::render_html(&deck, &title))?;
+
+    Ok(())
+}
+
+fn fmt(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let check = matches.is_present("check");
+    let mut unformatted = 0;
+
+    for path in card_paths(matches)? {
+        if format::format_data_file(&path, check)? {
+            unformatted += 1;
+            if check {
+                println!("{} needs formatting", path.display());
             } else {
-                Ok(())
+                println!("Formatted {}", path.display());
             }
-        })
-        .map_err(|_| String::from("The value must be a natural number"))
-        .and_then(convert::identity)
+        }
+    }
+
+    if check && unformatted != 0 {
+        exit(1)
+    }
+
+    Ok(())
+}
+
+fn dedupe(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let dedupe = Dedupe::load(card_paths(matches)?)?;
+
+    for duplicate in dedupe.duplicates() {
+        let first = dedupe.card(duplicate.occurrences[0]);
+        if dedupe.is_conflict(duplicate) {
+            println!("{} (conflict)", first.recto().join(" OR "));
+        } else {
+            println!("{}", first.recto().join(" OR "));
+        }
+
+        for &(file_index, card_index) in &duplicate.occurrences {
+            println!(
+                "    {}:{} -> {}",
+                dedupe.path(file_index).display(),
+                card_index + 1,
+                dedupe.card((file_index, card_index)).verso().join(" OR ")
+            );
+        }
+    }
+    println!("{} duplicated card(s) found.", dedupe.duplicates().len());
+
+    if matches.is_present("merge") {
+        for path in dedupe.merge()? {
+            println!("Merged duplicates in {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+// Every data file given by the user, folders being read recursively
+fn card_paths(matches: &ArgMatches) -> Result<Vec<PathBuf>, io::Error> {
+    let mut paths = Vec::new();
+    for input in matches.values_of("card_path").unwrap() {
+        paths.append(&mut data_file_paths(Path::new(input))?);
+    }
+    paths.sort();
+    paths.dedup();
+
+    Ok(paths)
+}
+
+#[inline]
+fn load_data_files(matches: &ArgMatches) -> Result<Vec<DataFile>, io::Error> {
+    card_paths(matches)?
+        .iter()
+        .map(|path| load_data_file(path))
+        .collect()
+}
+
+fn write_output(matches: &ArgMatches, content: &str) -> Result<(), io::Error> {
+    match matches.value_of("output") {
+        Some(output) => fs::write(output, content),
+        None => {
+            print!("{}", content);
+            Ok(())
+        }
+    }
 }
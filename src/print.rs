@@ -0,0 +1,112 @@
+use std::fmt::Write;
+
+use pixo::card::{Card, Sides};
+use pixo::deck::Deck;
+
+const COLUMNS: usize = 3;
+const ROWS: usize = 4;
+const CARDS_PER_PAGE: usize = COLUMNS * ROWS;
+
+const STYLE: &str = "
+@page { size: A4; margin: 10mm; }
+* { box-sizing: border-box; }
+body { margin: 0; font-family: sans-serif; }
+.page { display: grid; grid-template-columns: repeat(3, 1fr); grid-template-rows: repeat(4, 1fr);
+    width: 190mm; height: 277mm; page-break-after: always; break-after: page; }
+.page:last-child { page-break-after: auto; break-after: auto; }
+.card { border: 1px dashed #999; padding: 4mm; display: flex; flex-direction: column;
+    justify-content: center; align-items: center; text-align: center; overflow: hidden; }
+.card.empty { border-color: transparent; }
+.main { font-size: 14pt; }
+.main p { margin: 1mm 0; }
+.tip { margin-top: 3mm; font-size: 8pt; color: #555; }
+.side-note { margin-top: 3mm; font-size: 7pt; color: #999; }
+";
+
+/// Render the deck as a self-contained HTML page of double-sided cards.
+///
+/// Each recto page is followed by its verso page, whose rows are mirrored so
+/// that both faces line up once the sheet is printed in duplex (long edge).
+pub fn render_html(deck: &Deck, title: &str) -> String {
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(html, "<title>{}</title>", escape(title)).unwrap();
+    writeln!(html, "<style>{}</style>", STYLE).unwrap();
+    html.push_str("</head>\n<body>\n");
+
+    for page in deck.cards().chunks(CARDS_PER_PAGE) {
+        html.push_str("<div class=\"page\">\n");
+        for slot in 0..CARDS_PER_PAGE {
+            write_recto(&mut html, page.get(slot));
+        }
+        html.push_str("</div>\n");
+
+        html.push_str("<div class=\"page\">\n");
+        for slot in 0..CARDS_PER_PAGE {
+            // Mirror each row so the verso is printed behind its recto
+            let row = slot / COLUMNS;
+            let column = COLUMNS - 1 - slot % COLUMNS;
+            write_verso(&mut html, page.get(row * COLUMNS + column));
+        }
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+fn write_recto(html: &mut String, card: Option<&Card>) {
+    match card {
+        // The recto of an only_verso card is never asked, so its tip is useless
+        Some(card) if card.sides == Sides::OnlyVerso => {
+            write_face(html, &card.recto, &[], Some("verso only"))
+        }
+        Some(card) => write_face(html, &card.recto, card.tip.recto(), None),
+        None => html.push_str("<div class=\"card empty\"></div>\n"),
+    }
+}
+
+fn write_verso(html: &mut String, card: Option<&Card>) {
+    match card {
+        // Likewise for the verso of an only_recto card
+        Some(card) if card.sides == Sides::OnlyRecto => {
+            write_face(html, &card.verso, &[], Some("recto only"))
+        }
+        Some(card) => write_face(html, &card.verso, card.tip.verso(), None),
+        None => html.push_str("<div class=\"card empty\"></div>\n"),
+    }
+}
+
+fn write_face(html: &mut String, sides: &[String], hints: &[String], note: Option<&str>) {
+    html.push_str("<div class=\"card\">\n<div class=\"main\">");
+    for side in sides {
+        write!(html, "<p>{}</p>", escape(side)).unwrap();
+    }
+    html.push_str("</div>\n");
+
+    if !hints.is_empty() {
+        writeln!(html, "<div class=\"tip\">{}</div>", escape(&hints.join(" / "))).unwrap();
+    }
+    if let Some(note) = note {
+        writeln!(html, "<div class=\"side-note\">{}</div>", note).unwrap();
+    }
+
+    html.push_str("</div>\n");
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
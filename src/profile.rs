@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rand::Rng;
+use serde::Deserialize;
+
+use pixo::ask::{AskerBuilder, FlipMode, HintMode, Matching, RandomFlip, SchedulerKind, Tries};
+use pixo::{leitner::Leitner, xdg};
+
+const CONFIG_FILE: &str = "config.json";
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The configuration file, `$XDG_CONFIG_HOME/pixo/config.json`.
+#[derive(Deserialize, Default)]
+pub struct Config {
+    #[serde(default)]
+    profiles: HashMap<String, Profile>,
+}
+
+/// Study settings, every missing one keeping its usual value.
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub flip_mode: Option<ProfileFlipMode>,
+    pub all_cases: Option<bool>,
+    // Part of the cards flipped in the random flip mode, from 0 to 1
+    pub random_ratio: Option<f64>,
+    // Flip each card independently in the random flip mode
+    pub coin_flips: Option<bool>,
+    pub tries: Option<u32>,
+    pub passes: Option<u32>,
+    // Cards asked in the session, and never answered ones among them
+    pub limit: Option<u32>,
+    pub new: Option<u32>,
+    // Correct answers in a row needed to master a card, replacing the passes
+    pub mastery: Option<u32>,
+    // Number of Leitner boxes, replacing the passes
+    pub leitner: Option<u32>,
+    pub scheduler: Option<SchedulerKind>,
+    // Seconds to answer each question
+    pub time_limit: Option<u64>,
+    // Minutes before the end of the session
+    pub session_time_limit: Option<u64>,
+    pub ignore_case: Option<bool>,
+    pub ignore_punctuation: Option<bool>,
+    pub copy: Option<bool>,
+    pub cloze: Option<bool>,
+    // Only ask the cards with at least one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Never ask the cards with one of these tags
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileFlipMode {
+    Recto,
+    Verso,
+    Random,
+}
+
+impl Config {
+    pub fn load() -> Result<Self, io::Error> {
+        match Self::path() {
+            Some(path) if path.is_file() => {
+                let buf_reader = BufReader::new(File::open(path)?);
+                Ok(serde_json::from_reader(buf_reader)?)
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    #[inline]
+    pub fn path() -> Option<PathBuf> {
+        xdg::config_dir().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    /// Get a profile of the configuration file. The `default` profile exists
+    /// even if the file does not define it.
+    pub fn profile(&self, name: &str) -> Option<Profile> {
+        self.profiles.get(name).cloned().or_else(|| {
+            if name == DEFAULT_PROFILE {
+                Some(Profile::default_profile())
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl Profile {
+    pub fn default_profile() -> Self {
+        Self {
+            flip_mode: Some(ProfileFlipMode::Random),
+            all_cases: Some(true),
+            tries: Some(2),
+            passes: Some(2),
+            ..Self::default()
+        }
+    }
+
+    // Return false if the scheduler can not ask every card in both directions
+    #[inline]
+    pub fn supports_all_cases(&self) -> bool {
+        self.leitner.and_then(NonZeroU32::new).is_none()
+            && self.scheduler.unwrap_or_default().supports_all_cases()
+    }
+
+    /// Set up a session with the settings of the profile, the tags excepted.
+    pub fn configure<R: Rng>(&self, asker: &mut AskerBuilder<R>) -> Result<(), Box<dyn Error>> {
+        let random_flip = RandomFlip::new(
+            self.random_ratio.unwrap_or(RandomFlip::DEFAULT_RATIO),
+            self.coin_flips.unwrap_or(false),
+            self.all_cases.unwrap_or(false),
+        )?;
+        if random_flip.is_all_cases()
+            && matches!(self.flip_mode, Some(ProfileFlipMode::Random))
+            && !self.supports_all_cases()
+        {
+            return Err("all_cases needs the passes or the sequential scheduler".into());
+        }
+        match self.flip_mode {
+            Some(ProfileFlipMode::Verso) => asker.flip_mode(FlipMode::Verso),
+            Some(ProfileFlipMode::Random) => asker.flip_mode(FlipMode::Random(random_flip)),
+            Some(ProfileFlipMode::Recto) | None => (),
+        }
+
+        let passes = self.passes.unwrap_or(1);
+        asker.max_cycle(NonZeroU32::new(passes).ok_or("The number of passes must be at lease 1")?);
+        if let Some(limit) = self.limit.and_then(NonZeroU32::new) {
+            asker.limit(limit);
+        }
+        if let Some(new_cards) = self.new {
+            asker.new_cards(new_cards);
+        }
+
+        if let Some(mastery) = self.mastery.and_then(NonZeroU32::new) {
+            asker.mastery(mastery);
+        }
+        if let Some(scheduler) = self.scheduler {
+            asker.scheduler_kind(scheduler);
+        }
+        if let Some(boxes) = self.leitner.and_then(NonZeroU32::new) {
+            asker.leitner(Leitner::load(boxes)?);
+        }
+
+        asker.tries(Tries::from_count(self.tries.unwrap_or(1)));
+
+        if let Some(time) = self.time_limit.filter(|time| *time != 0) {
+            asker.time_limit(Duration::from_secs(time));
+        }
+        if let Some(session_time) = self.session_time_limit.filter(|time| *time != 0) {
+            asker.session_time_limit(Duration::from_secs(session_time * 60));
+        }
+
+        asker.matching(Matching {
+            ignore_case: self.ignore_case.unwrap_or(false),
+            ignore_punctuation: self.ignore_punctuation.unwrap_or(false),
+        });
+        asker.copy(self.copy.unwrap_or(false));
+        if self.cloze.unwrap_or(false) {
+            asker.hint_mode(HintMode::Cloze);
+        }
+
+        Ok(())
+    }
+}
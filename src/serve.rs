@@ -0,0 +1,383 @@
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::slice;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use rand::rngs::ThreadRng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use pixo::ask::{AskerBuilder, Engine};
+use pixo::deck::Deck;
+use pixo::history::History;
+use pixo::load::{load_data_file, DataFile};
+use pixo::stats::Stats;
+
+use crate::profile::Config;
+
+// Biggest request body accepted
+const MAX_BODY: usize = 1 << 20;
+// Time given to a client to send its request, so an idle connection does not
+// block the others
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Status code and message sent back as `{"error": message}`
+type HttpError = (u16, String);
+
+/// Serve one study session at a time over a HTTP JSON API, on localhost only :
+/// - `POST /deck` with `{"path": "deck.json", "profile": "name"}` starts a session,
+///   the profile being optional
+/// - `GET /question` returns the current question, or `null` once the session is over
+/// - `POST /answer` with `{"answer": "..."}`, and `POST /giveup`, return the outcome
+/// - `GET /stats` returns the statistics of the deck
+///
+/// Web pages served from localhost, on any port, are allowed to call the API,
+/// the requests from other origins, or to another host, being rejected.
+pub fn serve(port: u16) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    let address = listener.local_addr()?;
+    println!("Listening on http://{}", address);
+
+    // Each request is read in its own thread, so a slow client does not block
+    // the others, then answered in this one, which owns the session
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let sender = sender.clone();
+                    thread::spawn(move || {
+                        let request = receive(&stream);
+                        // The receiver lives as long as the server
+                        let _ = sender.send((stream, request));
+                    });
+                }
+                Err(error) => eprintln!("{}", error),
+            }
+        }
+    });
+
+    let mut server = Server::new(address.port());
+    for (stream, request) in receiver {
+        if let Err(error) = server.handle(&stream, request) {
+            eprintln!("{}", error);
+        }
+    }
+
+    Ok(())
+}
+
+struct Server {
+    session: Option<Session>,
+    // Port of the server, the only one accepted in the Host header
+    port: u16,
+}
+
+struct Session {
+    engine: Engine<ThreadRng>,
+    data_file: DataFile,
+    // The history and the scheduler were saved
+    finished: bool,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    host: Option<String>,
+    // Origin of the web page sending the request, if any
+    origin: Option<String>,
+    body: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeckRequest {
+    path: PathBuf,
+    #[serde(default)]
+    profile: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnswerRequest {
+    answer: String,
+}
+
+impl Server {
+    #[inline]
+    const fn new(port: u16) -> Self {
+        Self {
+            session: None,
+            port,
+        }
+    }
+
+    fn handle(
+        &mut self,
+        stream: &TcpStream,
+        request: Result<Request, io::Error>,
+    ) -> Result<(), io::Error> {
+        let request = match request {
+            Ok(request) => request,
+            Err(error) => {
+                let body = json!({ "error": error.to_string() });
+                return write_response(stream, 400, Some(&body), None);
+            }
+        };
+        // A web page can send requests to another host resolving to this
+        // computer, or send simple requests from any origin
+        if !request
+            .host
+            .as_deref()
+            .is_some_and(|host| self.is_own_host(host))
+        {
+            let body = json!({ "error": "The host is not the server." });
+            return write_response(stream, 403, Some(&body), None);
+        }
+        let origin = request.origin.as_deref();
+        if origin.is_some_and(|origin| !is_local(origin)) {
+            let body =
+                json!({ "error": "Only the web pages served from localhost can call the API." });
+            return write_response(stream, 403, Some(&body), None);
+        }
+
+        // Preflight request of the browsers before a JSON `POST`
+        if request.method == "OPTIONS" {
+            return write_response(stream, 204, None, origin);
+        }
+
+        let (status, body) = match self.route(&request) {
+            Ok(body) => (200, body),
+            Err((status, message)) => (status, json!({ "error": message })),
+        };
+        write_response(stream, status, Some(&body), origin)
+    }
+
+    fn route(&mut self, request: &Request) -> Result<Value, HttpError> {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("POST", "/deck") => self.load_deck(parse(&request.body)?),
+            ("GET", "/question") => to_value(self.session()?.engine.next_question()),
+            ("POST", "/answer") => {
+                let request: AnswerRequest = parse(&request.body)?;
+                let session = self.session()?;
+                let outcome = session.engine.submit_answer(&request.answer);
+                session.finish_if_over()?;
+                to_value(outcome)
+            }
+            ("POST", "/giveup") => {
+                let session = self.session()?;
+                let outcome = session.engine.give_up();
+                session.finish_if_over()?;
+                to_value(outcome)
+            }
+            ("GET", "/stats") => {
+                let session = self.session()?;
+                let history = session.engine.history().unwrap();
+                to_value(Stats::new(slice::from_ref(&session.data_file), history))
+            }
+            _ => Err((
+                404,
+                format!("There is no route {} {}.", request.method, request.path),
+            )),
+        }
+    }
+
+    fn load_deck(&mut self, request: DeckRequest) -> Result<Value, HttpError> {
+        let profile = match &request.profile {
+            Some(name) => Config::load()
+                .map_err(internal_error)?
+                .profile(name)
+                .ok_or_else(|| (400, format!("The profile {:?} does not exist.", name)))?,
+            None => Default::default(),
+        };
+
+        let mut data_file =
+            load_data_file(&request.path).map_err(|error| (400, error.to_string()))?;
+        data_file.filter_tags(&profile.tags, &profile.exclude_tags);
+
+        // End the previous session first, to save its history
+        if let Some(session) = self.session.as_mut().filter(|session| !session.finished) {
+            session.engine.finish().map_err(internal_error)?;
+        }
+
+        let mut asker = AskerBuilder::new(Deck::from(data_file.clone()), rand::thread_rng());
+        asker.history(History::load().map_err(internal_error)?);
+        profile
+            .configure(&mut asker)
+            .map_err(|error| (400, error.to_string()))?;
+
+        let mut session = Session {
+            engine: asker.build_engine(),
+            data_file,
+            finished: false,
+        };
+        session.finish_if_over()?;
+        let cards = session.engine.deck().len();
+        self.session = Some(session);
+
+        Ok(json!({ "cards": cards }))
+    }
+
+    // Return true if the Host header names this server
+    fn is_own_host(&self, host: &str) -> bool {
+        ["localhost", "127.0.0.1", "[::1]"]
+            .iter()
+            .any(|name| host == format!("{}:{}", name, self.port))
+    }
+
+    #[inline]
+    fn session(&mut self) -> Result<&mut Session, HttpError> {
+        self.session
+            .as_mut()
+            .ok_or_else(|| (409, "There is no deck loaded.".to_owned()))
+    }
+}
+
+impl Session {
+    // Save the history and the scheduler once the session is over
+    fn finish_if_over(&mut self) -> Result<(), HttpError> {
+        if !self.finished && self.engine.next_question().is_none() {
+            self.finished = true;
+            self.engine.finish().map_err(internal_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[inline]
+fn receive(stream: &TcpStream) -> Result<Request, io::Error> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    read_request(&mut BufReader::new(stream))
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> Result<Request, io::Error> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut words = line.split_whitespace();
+    let method = words
+        .next()
+        .ok_or_else(|| invalid("The request is empty."))?
+        .to_owned();
+    let target = words
+        .next()
+        .ok_or_else(|| invalid("The request has no path."))?;
+    // The query string is ignored
+    let path = target.split('?').next().unwrap().to_owned();
+
+    let mut content_length = 0;
+    let mut host = None;
+    let mut origin = None;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid("The Content-Length is not a number."))?;
+            } else if name.trim().eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_owned());
+            } else if name.trim().eq_ignore_ascii_case("origin") {
+                origin = Some(value.trim().to_owned());
+            }
+        }
+    }
+    if content_length > MAX_BODY {
+        return Err(invalid("The request body is too big."));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Request {
+        method,
+        path,
+        host,
+        origin,
+        body,
+    })
+}
+
+// Write the response, allowing `origin` to read it if it is given
+fn write_response(
+    mut stream: &TcpStream,
+    status: u16,
+    body: Option<&Value>,
+    origin: Option<&str>,
+) -> Result<(), io::Error> {
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    };
+    let body = body.map(Value::to_string).unwrap_or_default();
+
+    let mut headers = String::new();
+    if let Some(origin) = origin {
+        headers.push_str(&format!(
+            "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\nAccess-Control-Allow-Headers: Content-Type\r\nVary: Origin\r\n",
+            origin
+        ));
+    }
+    if !body.is_empty() {
+        headers.push_str("Content-Type: application/json\r\n");
+    }
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        headers,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// Return true if the origin is a web page served from this computer
+fn is_local(origin: &str) -> bool {
+    let host = match origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    {
+        Some(host) => host,
+        None => return false,
+    };
+    // Without the port
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap(),
+        None => host.split(':').next().unwrap(),
+    };
+
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+#[inline]
+fn parse<'a, T: Deserialize<'a>>(body: &'a [u8]) -> Result<T, HttpError> {
+    serde_json::from_slice(body).map_err(|error| (400, error.to_string()))
+}
+
+#[inline]
+fn to_value<T: Serialize>(value: T) -> Result<Value, HttpError> {
+    serde_json::to_value(value).map_err(internal_error)
+}
+
+#[inline]
+fn internal_error<E: ToString>(error: E) -> HttpError {
+    (500, error.to_string())
+}
@@ -0,0 +1,60 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::xdg;
+
+const SESSION_FILE: &str = "session.json";
+
+// Checkpoint of the session being studied, written after each answer
+#[inline]
+fn path() -> Option<PathBuf> {
+    xdg::data_dir().map(|dir| dir.join(SESSION_FILE))
+}
+
+pub fn save<T: Serialize>(session: &T) -> Result<(), io::Error> {
+    match path() {
+        Some(path) => write_json(&path, session),
+        None => Ok(()),
+    }
+}
+
+// Write then rename, so a session killed while saving keeps the previous file
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), io::Error> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string(value)?)?;
+    fs::rename(tmp, path)
+}
+
+/// Load the checkpoint of the last interrupted session, if there is one.
+pub fn load<T: DeserializeOwned>() -> Result<Option<T>, io::Error> {
+    match path() {
+        Some(path) if path.is_file() => {
+            let buf_reader = BufReader::new(File::open(path)?);
+            Ok(Some(serde_json::from_reader(buf_reader)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+// Remove the checkpoint once the session is over
+pub fn remove() -> Result<(), io::Error> {
+    match path() {
+        Some(path) if path.is_file() => fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+// Check that a value is saved and loaded unchanged, returning the loaded value
+#[cfg(test)]
+pub(crate) fn assert_round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).unwrap();
+    let loaded: T = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+    loaded
+}
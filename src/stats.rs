@@ -0,0 +1,228 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::card::{Direction, Sides, Tip};
+use crate::history::{CardKey, History, Review};
+use crate::load::DataFile;
+
+const DAY: u64 = 24 * 60 * 60;
+const SLOWEST_CARDS: usize = 5;
+
+// Upper bounds of the delays between two reviews used by the retention curve
+const RETENTION_BUCKETS: [(u64, &str); 5] = [
+    (DAY, "< 1 day"),
+    (3 * DAY, "1-3 days"),
+    (7 * DAY, "3-7 days"),
+    (28 * DAY, "1-4 weeks"),
+    (u64::MAX, "> 4 weeks"),
+];
+
+#[derive(Default, Serialize)]
+pub struct Stats {
+    cards: usize,
+    tags: BTreeMap<String, usize>,
+    only_recto: usize,
+    only_verso: usize,
+    both_required: usize,
+    tip_none: usize,
+    tip_one: usize,
+    tip_recto_verso: usize,
+    answers: usize,
+    // (card, correct answers, reviews, average response time in milliseconds),
+    // for each direction of the cards
+    accuracy: Vec<(CardKey, usize, usize, Option<u64>)>,
+    // (correct answers, reviews) forward and reverse
+    directions: [(usize, usize); 2],
+    // (correct answers, reviews) for each retention bucket
+    retention: [(usize, usize); RETENTION_BUCKETS.len()],
+}
+
+impl Stats {
+    pub fn new(data_files: &[DataFile], history: &History) -> Self {
+        let mut stats = Self::default();
+
+        let cards = data_files.iter().flat_map(|data_file| {
+            data_file
+                .questions()
+                .iter()
+                .map(move |card| (card, data_file.sides_of(card)))
+        });
+        for (card, sides) in cards {
+            stats.cards += 1;
+            stats.answers += card.verso().len();
+
+            for tag in card.tags() {
+                *stats.tags.entry(tag.as_str().to_owned()).or_default() += 1;
+            }
+            match sides {
+                Sides::OnlyRecto => stats.only_recto += 1,
+                Sides::OnlyVerso => stats.only_verso += 1,
+                Sides::BothRequired => stats.both_required += 1,
+                Sides::Any => (),
+            }
+
+            match card.tip() {
+                Tip::None => stats.tip_none += 1,
+                Tip::One(_) => stats.tip_one += 1,
+                Tip::RectoVerso(_, _) => stats.tip_recto_verso += 1,
+            }
+
+            // The cards asked in one direction have no review in the other one
+            for (index, direction) in [Direction::Forward, Direction::Reverse].iter().enumerate() {
+                match (sides, direction) {
+                    (Sides::OnlyRecto, Direction::Reverse)
+                    | (Sides::OnlyVerso, Direction::Forward) => continue,
+                    _ => (),
+                }
+                let key = CardKey::new(card.recto(), *direction);
+                let reviews = history.reviews(&key);
+                if reviews.is_empty() {
+                    continue;
+                }
+
+                let correct = reviews.iter().filter(|review| review.correct).count();
+                let response_times = reviews
+                    .iter()
+                    .filter_map(|review| review.response_time)
+                    .collect::<Vec<_>>();
+                let response_time = if response_times.is_empty() {
+                    None
+                } else {
+                    Some(response_times.iter().sum::<u64>() / response_times.len() as u64)
+                };
+
+                stats.directions[index].0 += correct;
+                stats.directions[index].1 += reviews.len();
+                stats
+                    .accuracy
+                    .push((key, correct, reviews.len(), response_time));
+                stats.add_retention(reviews);
+            }
+        }
+
+        stats
+    }
+
+    // Sort each review by the delay since the previous review of the same card
+    fn add_retention(&mut self, reviews: &[Review]) {
+        for pair in reviews.windows(2) {
+            let delay = pair[1].time.saturating_sub(pair[0].time);
+            let bucket = RETENTION_BUCKETS
+                .iter()
+                .position(|(bound, _)| delay < *bound)
+                .unwrap();
+
+            self.retention[bucket].1 += 1;
+            if pair[1].correct {
+                self.retention[bucket].0 += 1;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cards : {}", self.cards)?;
+        writeln!(f, "Only recto : {}", self.only_recto)?;
+        writeln!(f, "Only verso : {}", self.only_verso)?;
+        writeln!(f, "Both required : {}", self.both_required)?;
+        writeln!(
+            f,
+            "Tips : {} none, {} one, {} recto/verso",
+            self.tip_none, self.tip_one, self.tip_recto_verso
+        )?;
+        if self.cards != 0 {
+            writeln!(
+                f,
+                "Average accepted answers : {:.2}",
+                self.answers as f64 / self.cards as f64
+            )?;
+        }
+
+        if !self.tags.is_empty() {
+            writeln!(f, "Tags :")?;
+            for (tag, count) in &self.tags {
+                writeln!(f, "    {} : {}", tag, count)?;
+            }
+        }
+
+        if !self.accuracy.is_empty() {
+            writeln!(f, "Accuracy :")?;
+            for (card, correct, reviews, response_time) in &self.accuracy {
+                write!(
+                    f,
+                    "    {} : {}/{} ({})",
+                    card,
+                    correct,
+                    reviews,
+                    percent(*correct, *reviews)
+                )?;
+                match response_time {
+                    Some(response_time) => writeln!(f, ", {}", seconds(*response_time))?,
+                    None => writeln!(f)?,
+                }
+            }
+        }
+
+        if self.directions[1].1 != 0 {
+            writeln!(f, "Directions :")?;
+            for (name, (correct, reviews)) in ["recto -> verso", "verso -> recto"]
+                .iter()
+                .zip(&self.directions)
+            {
+                if *reviews != 0 {
+                    writeln!(
+                        f,
+                        "    {} : {} over {} reviews",
+                        name,
+                        percent(*correct, *reviews),
+                        reviews
+                    )?;
+                }
+            }
+        }
+
+        let mut slowest = self
+            .accuracy
+            .iter()
+            .filter_map(|(card, _, _, response_time)| response_time.map(|time| (card, time)))
+            .collect::<Vec<_>>();
+        if !slowest.is_empty() {
+            slowest.sort_by_key(|(_, response_time)| Reverse(*response_time));
+            writeln!(f, "Slowest cards :")?;
+            for (card, response_time) in slowest.iter().take(SLOWEST_CARDS) {
+                writeln!(f, "    {} : {}", card, seconds(*response_time))?;
+            }
+        }
+
+        if self.retention.iter().any(|(_, reviews)| *reviews != 0) {
+            writeln!(f, "Retention (delay since the previous review) :")?;
+            for ((_, name), (correct, reviews)) in RETENTION_BUCKETS.iter().zip(&self.retention) {
+                if *reviews != 0 {
+                    writeln!(
+                        f,
+                        "    {} : {} over {} reviews",
+                        name,
+                        percent(*correct, *reviews),
+                        reviews
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[inline]
+fn seconds(milliseconds: u64) -> String {
+    format!("{:.1}s", milliseconds as f64 / 1000.)
+}
+
+#[inline]
+fn percent(part: usize, total: usize) -> String {
+    format!("{:.0}%", part as f64 * 100. / total as f64)
+}